@@ -0,0 +1,252 @@
+use chrono::{NaiveDate, Utc};
+use chrono_tz::Tz;
+use itertools::Itertools;
+use std::rc::Rc;
+use std::str::FromStr;
+use yew::prelude::*;
+use yew_router::prelude::*;
+
+use crate::event_calendar::EventItem;
+use crate::event_manager::EventManager;
+use crate::events::ScheduledEvent;
+use crate::search_client::MediaType;
+use crate::tags::Tags;
+use crate::time_zone::UserTimeZone;
+use crate::ui_helpers::UiHelpers;
+use crate::Route;
+
+// Every filter is optional, an empty filter lets everything through.
+#[derive(Clone, Default, PartialEq)]
+pub struct AgendaFilter {
+    pub search: String,
+    pub show: Option<String>,
+    pub media_type: Option<MediaType>,
+    pub watched: Option<bool>,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub tag: Option<String>,
+}
+
+fn show_name(se: &ScheduledEvent) -> String {
+    match (&se.episode, &se.movie) {
+        (Some(ep), _) => ep.show_name.clone(),
+        (_, Some(mv)) => mv.show_name.clone(),
+        _ => String::from("Unknown"),
+    }
+}
+
+impl AgendaFilter {
+    fn matches(&self, se: &ScheduledEvent, tz: &Tz, tags: &Tags) -> bool {
+        let day = se.scheduled_date.with_timezone(tz).date_naive();
+        let name = show_name(se);
+        let search = self.search.trim().to_lowercase();
+        let found = search.is_empty()
+            || name.to_lowercase().contains(&search)
+            || se
+                .episode
+                .as_ref()
+                .is_some_and(|ep| ep.name.to_lowercase().contains(&search));
+
+        found
+            && self.show.as_ref().is_none_or(|show| *show == name)
+            && self.media_type.as_ref().is_none_or(|mt| *mt == se.media_type)
+            && self.watched.is_none_or(|watched| watched == se.watched())
+            && self.from.is_none_or(|from| day >= from)
+            && self.to.is_none_or(|to| day <= to)
+            && self.tag.as_ref().is_none_or(|tag| tags.has_tag(se, tag))
+    }
+}
+
+pub struct Agenda {
+    filter: AgendaFilter,
+    time_zone: Tz,
+    // Nothing here changes them, so they're only read once.
+    events: Vec<ScheduledEvent>,
+    tags: Rc<Tags>,
+}
+
+pub enum AgendaMsg {
+    Search(String),
+    FilterShow(Option<String>),
+    FilterMediaType(Option<MediaType>),
+    FilterWatched(Option<bool>),
+    FilterFrom(Option<NaiveDate>),
+    FilterTo(Option<NaiveDate>),
+    FilterTag(Option<String>),
+    Reset,
+    Open(Box<ScheduledEvent>),
+}
+
+impl Agenda {
+    // Upcoming events only until told otherwise.
+    fn default_filter(tz: &Tz) -> AgendaFilter {
+        AgendaFilter {
+            from: Some(Utc::now().with_timezone(tz).date_naive()),
+            ..AgendaFilter::default()
+        }
+    }
+}
+
+impl Component for Agenda {
+    type Message = AgendaMsg;
+    type Properties = ();
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        let time_zone = UserTimeZone::get();
+        let mut events = EventManager::create().events;
+        events.sort_by_key(|se| se.scheduled_date);
+
+        Self {
+            filter: Self::default_filter(&time_zone),
+            time_zone,
+            events,
+            tags: Rc::new(Tags::load()),
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            AgendaMsg::Search(search) => self.filter.search = search,
+            AgendaMsg::FilterShow(show) => self.filter.show = show,
+            AgendaMsg::FilterMediaType(media_type) => self.filter.media_type = media_type,
+            AgendaMsg::FilterWatched(watched) => self.filter.watched = watched,
+            AgendaMsg::FilterFrom(from) => self.filter.from = from,
+            AgendaMsg::FilterTo(to) => self.filter.to = to,
+            AgendaMsg::FilterTag(tag) => self.filter.tag = tag,
+            AgendaMsg::Reset => self.filter = Self::default_filter(&self.time_zone),
+            AgendaMsg::Open(se) => {
+                // The calendar has the details, rescheduling and everything else.
+                if let Some(navigator) = ctx.link().navigator() {
+                    navigator.push(&Route::Event { id: se.uuid });
+                }
+                return false;
+            }
+        }
+
+        true
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let tz = self.time_zone;
+        let filter = &self.filter;
+        let events = &self.events;
+        let shows = events.iter().map(show_name).unique().sorted().collect::<Vec<String>>();
+        let tags = &self.tags;
+        let all_tags = tags.all_tags(events);
+        let listed = events
+            .iter()
+            .filter(|se| filter.matches(se, &tz, tags))
+            .collect::<Vec<&ScheduledEvent>>();
+
+        let link = ctx.link();
+        let value = |e: &Event| UiHelpers::get_value_from_event(e).filter(|v| !v.is_empty());
+        let onsearch = link.callback(move |ie: InputEvent| AgendaMsg::Search(value(&ie).unwrap_or_default()));
+        let onshow = link.callback(move |e: Event| AgendaMsg::FilterShow(value(&e)));
+        let onmediatype = link.callback(move |e: Event| {
+            AgendaMsg::FilterMediaType(value(&e).and_then(|v| MediaType::from_str(&v).ok()))
+        });
+        let onwatched = link.callback(move |e: Event| {
+            AgendaMsg::FilterWatched(value(&e).and_then(|v| v.parse::<bool>().ok()))
+        });
+        let onfrom = link.callback(move |e: Event| {
+            AgendaMsg::FilterFrom(value(&e).and_then(|v| NaiveDate::from_str(&v).ok()))
+        });
+        let onto = link.callback(move |e: Event| {
+            AgendaMsg::FilterTo(value(&e).and_then(|v| NaiveDate::from_str(&v).ok()))
+        });
+        let ontag = link.callback(move |e: Event| AgendaMsg::FilterTag(value(&e)));
+        let onreset = link.callback(|_| AgendaMsg::Reset);
+        let onclick_event = link.batch_callback(|se: Option<ScheduledEvent>| se.map(|se| AgendaMsg::Open(Box::new(se))));
+        let date_value = |d: Option<NaiveDate>| d.map(|d| d.to_string()).unwrap_or_default();
+
+        let days = listed
+            .iter()
+            .group_by(|se| se.scheduled_date.with_timezone(&tz).date_naive())
+            .into_iter()
+            .map(|(day, day_events)| {
+                let items = day_events
+                    .map(|&se| {
+                        html! {
+                            <EventItem
+                                scheduled_event={se.clone()}
+                                time_zone={tz}
+                                onclick={onclick_event.clone()}
+                                tags={tags.clone()}
+                            />
+                        }
+                    })
+                    .collect::<Html>();
+
+                html! {
+                    <div class="agenda-day">
+                        <p class="subtitle">{day.format("%A, %B %d %Y").to_string()}</p>
+                        {items}
+                    </div>
+                }
+            })
+            .collect::<Html>();
+
+        html! {
+            <div class="box agenda">
+                <div class="field is-grouped is-grouped-multiline">
+                    <p class="control is-expanded">
+                        <input class="input" type="text" placeholder="Search shows and episodes"
+                            value={filter.search.clone()} oninput={onsearch} />
+                    </p>
+                    <div class="control">
+                        <div class="select">
+                            <select onchange={onshow}>
+                                <option value="" selected={filter.show.is_none()}>{"All Shows"}</option>
+                                {shows.iter().map(|show| html!{
+                                    <option value={show.clone()} selected={filter.show.as_ref() == Some(show)}>{show}</option>
+                                }).collect::<Html>()}
+                            </select>
+                        </div>
+                    </div>
+                    <div class="control">
+                        <div class="select">
+                            <select onchange={onmediatype}>
+                                <option value="" selected={filter.media_type.is_none()}>{"TV & Movies"}</option>
+                                <option value="tv" selected={filter.media_type == Some(MediaType::tv)}>{"TV"}</option>
+                                <option value="movie" selected={filter.media_type == Some(MediaType::movie)}>{"Movies"}</option>
+                            </select>
+                        </div>
+                    </div>
+                    <div class="control">
+                        <div class="select">
+                            <select onchange={onwatched}>
+                                <option value="" selected={filter.watched.is_none()}>{"Watched & Unwatched"}</option>
+                                <option value="false" selected={filter.watched == Some(false)}>{"Unwatched"}</option>
+                                <option value="true" selected={filter.watched == Some(true)}>{"Watched"}</option>
+                            </select>
+                        </div>
+                    </div>
+                    <div class="control">
+                        <div class="select">
+                            <select onchange={ontag}>
+                                <option value="" selected={filter.tag.is_none()}>{"All Tags"}</option>
+                                {all_tags.iter().map(|tag| html!{
+                                    <option value={tag.clone()} selected={filter.tag.as_ref() == Some(tag)}>{tag}</option>
+                                }).collect::<Html>()}
+                            </select>
+                        </div>
+                    </div>
+                    <p class="control">
+                        <input class="input" type="date" title="From" value={date_value(filter.from)} onchange={onfrom} />
+                    </p>
+                    <p class="control">
+                        <input class="input" type="date" title="To" value={date_value(filter.to)} onchange={onto} />
+                    </p>
+                    <p class="control">
+                        <button class="button" onclick={onreset}>{"Reset"}</button>
+                    </p>
+                </div>
+                <p class="is-size-7 mb-2">{format!("{} of {} events", listed.len(), events.len())}</p>
+                if listed.is_empty() {
+                    <p>{"Nothing scheduled matches these filters."}</p>
+                }
+                {days}
+            </div>
+        }
+    }
+}
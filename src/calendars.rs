@@ -0,0 +1,302 @@
+use gloo::storage::errors::StorageError;
+use gloo::storage::{LocalStorage, Storage};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use weblog::console_error;
+use yew::prelude::*;
+
+use crate::event_manager::EventManager;
+use crate::events::ScheduledEvent;
+use crate::site_config::ByngerStore;
+use crate::ui_helpers::UiHelpers;
+
+// The calendar everything lived in before there were more than one, and where
+// events from calendars that no longer exist (or someone else's) end up.
+pub const DEFAULT_CALENDAR: Uuid = Uuid::nil();
+const DEFAULT_COLOR: &str = "#3e8ed0";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Calendar {
+    pub id: Uuid,
+    pub name: String,
+    pub color: String, // CSS hex, e.g. #3e8ed0
+}
+
+// Every event lives in one list, each tagged with the calendar it belongs to.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Calendars {
+    pub calendars: Vec<Calendar>,
+    pub active: Uuid,
+    #[serde(default)]
+    pub overlay: bool, // Show every calendar at once, each in its own color.
+    #[serde(default)]
+    pub hidden: Vec<Uuid>, // Calendars left out of the overlay.
+}
+
+impl Default for Calendars {
+    fn default() -> Self {
+        Calendars {
+            calendars: vec![Calendar {
+                id: DEFAULT_CALENDAR,
+                name: String::from("My Calendar"),
+                color: String::from(DEFAULT_COLOR),
+            }],
+            active: DEFAULT_CALENDAR,
+            overlay: false,
+            hidden: vec![],
+        }
+    }
+}
+
+impl Calendars {
+    pub fn load() -> Self {
+        let mut calendars: Calendars =
+            LocalStorage::get(ByngerStore::Calendars.to_string()).unwrap_or_default();
+        if !calendars.calendars.iter().any(|c| c.id == DEFAULT_CALENDAR) {
+            calendars.calendars.insert(0, Calendars::default().calendars.remove(0));
+        }
+        if calendars.get(calendars.active).is_none() {
+            calendars.active = DEFAULT_CALENDAR;
+        }
+
+        calendars
+    }
+
+    pub fn store(&self) -> Result<(), StorageError> {
+        LocalStorage::set(ByngerStore::Calendars.to_string(), self)
+    }
+
+    pub fn get(&self, id: Uuid) -> Option<&Calendar> {
+        self.calendars.iter().find(|c| c.id == id)
+    }
+
+    // The calendar an event is shown in, unknown calendars fall back to the default.
+    pub fn calendar_of(&self, se: &ScheduledEvent) -> &Calendar {
+        self.get(se.calendar)
+            .or_else(|| self.get(DEFAULT_CALENDAR))
+            .expect("Default calendar always exists")
+    }
+
+    pub fn shows(&self, se: &ScheduledEvent) -> bool {
+        let id = self.calendar_of(se).id;
+        match self.overlay {
+            true => !self.hidden.contains(&id),
+            false => id == self.active,
+        }
+    }
+
+    // Colors only matter when more than one calendar can be on screen.
+    pub fn color(&self, se: &ScheduledEvent) -> Option<String> {
+        self.overlay.then(|| self.calendar_of(se).color.clone())
+    }
+
+    pub fn add(&mut self, name: String, color: String) -> Uuid {
+        let id = Uuid::new_v4();
+        self.calendars.push(Calendar { id, name, color });
+
+        id
+    }
+
+    pub fn update(&mut self, id: Uuid, name: String, color: String) {
+        if let Some(calendar) = self.calendars.iter_mut().find(|c| c.id == id) {
+            calendar.name = name;
+            calendar.color = color;
+        }
+    }
+
+    // Removing a calendar hands its events back to the default one rather than deleting them.
+    pub fn remove(&mut self, id: Uuid) -> Result<(), StorageError> {
+        if id == DEFAULT_CALENDAR {
+            return Ok(());
+        }
+        let mut em = EventManager::create();
+        let moved = em
+            .events
+            .iter()
+            .filter(|se| se.calendar == id)
+            .map(|se| se.uuid)
+            .collect::<Vec<Uuid>>();
+        em.move_to_calendar(&moved, DEFAULT_CALENDAR)?;
+        self.calendars.retain(|c| c.id != id);
+        self.hidden.retain(|&h| h != id);
+        if self.active == id {
+            self.active = DEFAULT_CALENDAR;
+        }
+
+        self.store()
+    }
+}
+
+// A picker for which calendar new events go in to, defaulting to the given one.
+pub fn calendar_select(calendars: &Calendars, id: &str, selected: Uuid) -> Html {
+    html! {
+        <span class="select is-small">
+            <select id={id.to_string()} title="Calendar">
+                {calendars.calendars.iter().map(|c| html! {
+                    <option value={c.id.to_string()} selected={c.id == selected}>{&c.name}</option>
+                }).collect::<Html>()}
+            </select>
+        </span>
+    }
+}
+
+// What was picked in a calendar_select, the default calendar if nothing (valid) was.
+pub fn selected_calendar(calendars: &Calendars, id: &str) -> Uuid {
+    UiHelpers::get_value_from_input_by_id(id)
+        .and_then(|raw| Uuid::parse_str(&raw).ok())
+        .filter(|&id| calendars.get(id).is_some())
+        .unwrap_or(DEFAULT_CALENDAR)
+}
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct CalendarSwitcherProps {
+    pub onchange: Callback<()>,
+}
+
+pub struct CalendarSwitcher {
+    managing: bool,
+}
+
+pub enum CalendarSwitcherMsg {
+    Select(Uuid),
+    ToggleOverlay,
+    ToggleManaging,
+    Add,
+    Update,
+    Remove,
+}
+
+impl Component for CalendarSwitcher {
+    type Message = CalendarSwitcherMsg;
+    type Properties = CalendarSwitcherProps;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self { managing: false }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        let mut calendars = Calendars::load();
+        let name = || {
+            UiHelpers::get_value_from_input_by_id("#calendarName")
+                .map(|raw| raw.trim().to_string())
+                .filter(|name| !name.is_empty())
+        };
+        let color = || {
+            UiHelpers::get_value_from_input_by_id("#calendarColor")
+                .unwrap_or_else(|| String::from(DEFAULT_COLOR))
+        };
+        let stored = match msg {
+            // In overlay mode picking a calendar shows or hides it, otherwise it switches to it.
+            CalendarSwitcherMsg::Select(id) if calendars.overlay => {
+                if calendars.hidden.contains(&id) {
+                    calendars.hidden.retain(|&h| h != id);
+                } else {
+                    calendars.hidden.push(id);
+                }
+                calendars.active = id;
+                calendars.store()
+            }
+            CalendarSwitcherMsg::Select(id) => {
+                calendars.active = id;
+                calendars.store()
+            }
+            CalendarSwitcherMsg::ToggleOverlay => {
+                calendars.overlay = !calendars.overlay;
+                calendars.store()
+            }
+            CalendarSwitcherMsg::ToggleManaging => {
+                self.managing = !self.managing;
+                return true;
+            }
+            CalendarSwitcherMsg::Add => {
+                let Some(name) = name() else {
+                    return false;
+                };
+                calendars.active = calendars.add(name, color());
+                calendars.store()
+            }
+            CalendarSwitcherMsg::Update => {
+                let Some(name) = name() else {
+                    return false;
+                };
+                calendars.update(calendars.active, name, color());
+                calendars.store()
+            }
+            CalendarSwitcherMsg::Remove => calendars.remove(calendars.active),
+        };
+        if let Err(e) = stored {
+            console_error!(format!("BYNGER - Calendar Update Failed - {}", e));
+        }
+        ctx.props().onchange.emit(());
+
+        true
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let link = ctx.link();
+        let calendars = Calendars::load();
+        let active = calendars.get(calendars.active).cloned().unwrap_or_else(|| calendars.calendars[0].clone());
+        let buttons = calendars
+            .calendars
+            .iter()
+            .map(|c| {
+                let id = c.id;
+                let shown = match calendars.overlay {
+                    true => !calendars.hidden.contains(&id),
+                    false => id == calendars.active,
+                };
+                let mut class = classes!("button", "is-small", "calendar-choice");
+                if shown {
+                    class.push("is-selected");
+                }
+                html! {
+                    <button {class} onclick={link.callback(move |_| CalendarSwitcherMsg::Select(id))}>
+                        <span class="calendar-dot" style={format!("background-color: {};", c.color)}></span>
+                        {&c.name}
+                    </button>
+                }
+            })
+            .collect::<Html>();
+        let overlay_class = if calendars.overlay { "button is-small is-info" } else { "button is-small" };
+
+        html! {
+            <div class="calendar-switcher mb-2">
+                <div class="buttons mb-1">
+                    {buttons}
+                    <button class={overlay_class} title="Show every calendar at once"
+                        onclick={link.callback(|_| CalendarSwitcherMsg::ToggleOverlay)}>{"overlay"}</button>
+                    <button class="button is-small is-light"
+                        onclick={link.callback(|_| CalendarSwitcherMsg::ToggleManaging)}>{"calendars"}</button>
+                </div>
+                if self.managing {
+                    <div class="field is-grouped is-grouped-multiline">
+                        <p class="control">
+                            <input class="input is-small" id="calendarName" type="text" placeholder="Name"
+                                value={active.name.clone()} />
+                        </p>
+                        <p class="control">
+                            <input id="calendarColor" type="color" value={active.color.clone()} />
+                        </p>
+                        <p class="control">
+                            <button class="button is-small" onclick={link.callback(|_| CalendarSwitcherMsg::Update)}>
+                                {format!("Save \"{}\"", active.name)}
+                            </button>
+                        </p>
+                        <p class="control">
+                            <button class="button is-small is-success" onclick={link.callback(|_| CalendarSwitcherMsg::Add)}>
+                                {"Add As New"}
+                            </button>
+                        </p>
+                        if active.id != DEFAULT_CALENDAR {
+                            <p class="control">
+                                <button class="button is-small is-danger is-outlined"
+                                    title="Its events move to the default calendar"
+                                    onclick={link.callback(|_| CalendarSwitcherMsg::Remove)}>{"Remove"}</button>
+                            </p>
+                        }
+                    </div>
+                }
+            </div>
+        }
+    }
+}
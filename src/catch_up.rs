@@ -0,0 +1,214 @@
+use std::collections::BTreeSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use chrono::{DateTime, Duration, NaiveTime, Utc};
+use chrono_tz::Tz;
+use gloo::storage::{LocalStorage, Storage};
+use serde::{Deserialize, Serialize};
+use weblog::console_log;
+use yew::prelude::*;
+
+use crate::event_manager::EventManager;
+use crate::events::ScheduledEvent;
+use crate::site_config::ByngerStore;
+use crate::time_zone::UserTimeZone;
+
+// The policy is only applied once per app start, not every time the calendar shows up.
+static CAUGHT_UP_THIS_LOAD: AtomicBool = AtomicBool::new(false);
+
+// How far ahead we'll look for a free slot before giving up.
+const SLOT_SEARCH_DAYS: i64 = 366;
+
+// What to do with unwatched events that have already ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CatchUpStrategy {
+    // Moves each show's remaining episodes forward so the first missed one is next.
+    ShiftForward,
+    // Puts the missed episodes in the next free slots, keeping every show in episode order.
+    Reslot,
+}
+
+impl CatchUpStrategy {
+    // The strategy to run automatically on load, None to leave missed events alone.
+    pub fn policy() -> Option<Self> {
+        LocalStorage::get(ByngerStore::CatchUpPolicy.to_string()).ok()
+    }
+
+    // Applies the configured policy the first time it's called each app start.
+    pub fn apply_policy_on_load(tz: &Tz) {
+        if CAUGHT_UP_THIS_LOAD.swap(true, Ordering::Relaxed) {
+            return;
+        }
+        if let Some(strategy) = Self::policy() {
+            if let Ok(moved) = EventManager::create().catch_up(strategy, tz, Utc::now()) {
+                console_log!(format!("BYNGER - Caught up {moved} events ({strategy:?})"));
+            }
+        }
+    }
+}
+
+pub fn missed_events(events: &[ScheduledEvent], now: DateTime<Utc>) -> Vec<&ScheduledEvent> {
+    events
+        .iter()
+        .filter(|se| !se.watched() && se.ends_at() < now)
+        .collect()
+}
+
+fn show_id(se: &ScheduledEvent) -> Option<usize> {
+    se.episode.as_ref().map(|ep| ep.show_id)
+}
+
+// Specials (season 0) go last, the same as when scheduling a show.
+fn episode_order(se: &ScheduledEvent) -> (bool, usize, usize, DateTime<Utc>) {
+    se.episode.as_ref().map_or((false, 0, 0, se.scheduled_date), |ep| {
+        (ep.season_number == 0, ep.season_number, ep.episode_number, se.scheduled_date)
+    })
+}
+
+// Moves by whole days on the local calendar, so the time of day survives a DST change.
+fn shift_days(date: DateTime<Utc>, days: i64, tz: &Tz) -> DateTime<Utc> {
+    UserTimeZone::to_utc(tz, date.with_timezone(tz).naive_local() + Duration::days(days))
+}
+
+// Whole days to move `from` forward by so it's no longer in the past.
+fn days_until(from: DateTime<Utc>, now: DateTime<Utc>, tz: &Tz) -> i64 {
+    let mut days = (now - from).num_days();
+    if shift_days(from, days, tz) < now {
+        days += 1;
+    }
+
+    days
+}
+
+// The first time after `after` at the given local time of day that doesn't overlap anything busy.
+fn next_free_slot(
+    time_of_day: NaiveTime,
+    minutes: i64,
+    after: DateTime<Utc>,
+    busy: &[(DateTime<Utc>, DateTime<Utc>)],
+    tz: &Tz,
+) -> DateTime<Utc> {
+    let first_day = after.with_timezone(tz).date_naive();
+    (0..SLOT_SEARCH_DAYS)
+        .map(|offset| UserTimeZone::to_utc(tz, (first_day + Duration::days(offset)).and_time(time_of_day)))
+        .find(|&start| {
+            let end = start + Duration::minutes(minutes);
+            start >= after && !busy.iter().any(|&(b_start, b_end)| start < b_end && b_start < end)
+        })
+        .unwrap_or(after)
+}
+
+// Works out where everything touched by catching up should go, handing back only moved events.
+pub fn plan_catch_up(
+    events: &[ScheduledEvent],
+    strategy: CatchUpStrategy,
+    tz: &Tz,
+    now: DateTime<Utc>,
+) -> Vec<ScheduledEvent> {
+    let missed = missed_events(events, now);
+    let shows = missed.iter().filter_map(|se| show_id(se)).collect::<BTreeSet<usize>>();
+    let movies = missed
+        .iter()
+        .filter(|se| show_id(se).is_none())
+        .map(|&se| se.clone())
+        .collect::<Vec<ScheduledEvent>>();
+    // Anything not being moved keeps its slot, reslotting has to work around it.
+    let mut busy = events
+        .iter()
+        .filter(|se| !missed.iter().any(|m| m.uuid == se.uuid))
+        .map(|se| (se.scheduled_date, se.ends_at()))
+        .collect::<Vec<(DateTime<Utc>, DateTime<Utc>)>>();
+
+    let mut moved = vec![];
+    for show in shows {
+        let mut remaining = events
+            .iter()
+            .filter(|se| !se.watched() && show_id(se) == Some(show))
+            .cloned()
+            .collect::<Vec<ScheduledEvent>>();
+        remaining.sort_by_key(episode_order);
+
+        match strategy {
+            CatchUpStrategy::ShiftForward => {
+                let first = remaining.iter().map(|se| se.scheduled_date).min().unwrap_or(now);
+                let days = days_until(first, now, tz);
+                remaining
+                    .iter_mut()
+                    .for_each(|se| se.scheduled_date = shift_days(se.scheduled_date, days, tz));
+            }
+            CatchUpStrategy::Reslot => {
+                // The show keeps its upcoming slots and gains a new one per missed episode,
+                // then every remaining episode takes a slot in order.
+                let mut slots = remaining
+                    .iter()
+                    .filter(|se| se.ends_at() >= now)
+                    .map(|se| se.scheduled_date)
+                    .collect::<Vec<DateTime<Utc>>>();
+                let mut after = now;
+                for se in remaining.iter().filter(|se| se.ends_at() < now) {
+                    let local = se.scheduled_date.with_timezone(tz).time();
+                    let start = next_free_slot(local, se.runtime(), after, &busy, tz);
+                    busy.push((start, start + Duration::minutes(se.runtime())));
+                    slots.push(start);
+                    after = start;
+                }
+                slots.sort();
+                remaining
+                    .iter_mut()
+                    .zip(slots)
+                    .for_each(|(se, slot)| se.scheduled_date = slot);
+            }
+        }
+        moved.append(&mut remaining);
+    }
+
+    for mut movie in movies {
+        movie.scheduled_date = match strategy {
+            CatchUpStrategy::ShiftForward => {
+                shift_days(movie.scheduled_date, days_until(movie.scheduled_date, now, tz), tz)
+            }
+            CatchUpStrategy::Reslot => {
+                let local = movie.scheduled_date.with_timezone(tz).time();
+                let start = next_free_slot(local, movie.runtime(), now, &busy, tz);
+                busy.push((start, start + Duration::minutes(movie.runtime())));
+                start
+            }
+        };
+        moved.push(movie);
+    }
+
+    moved
+}
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct MissedEventsProps {
+    pub missed: usize,
+    pub oncatchup: Callback<CatchUpStrategy>,
+}
+
+#[function_component(MissedEvents)]
+pub fn missed_events_notice(props: &MissedEventsProps) -> Html {
+    if props.missed == 0 {
+        return html! {};
+    }
+    let ocu = props.oncatchup.clone();
+    let onshift = Callback::from(move |_| ocu.emit(CatchUpStrategy::ShiftForward));
+    let ocu = props.oncatchup.clone();
+    let onreslot = Callback::from(move |_| ocu.emit(CatchUpStrategy::Reslot));
+
+    html! {
+        <div class="notification is-warning missed-events">
+            <p>{format!("{} past event(s) went unwatched.", props.missed)}</p>
+            <div class="buttons mt-1">
+                <button class="button is-small" onclick={onshift}
+                    title="Move each show's remaining episodes forward so the first missed one is next">
+                    {"Push Shows Forward"}
+                </button>
+                <button class="button is-small" onclick={onreslot}
+                    title="Put missed episodes in the next free slots, keeping episode order">
+                    {"Reslot Missed"}
+                </button>
+            </div>
+        </div>
+    }
+}
@@ -1,6 +1,7 @@
 use chrono::{DateTime, Utc, Local, NaiveDate, NaiveTime};
 use uuid::Uuid;
 use yew::{Callback, Classes, classes, Component, Context, Html, html, Properties};
+use crate::time_zone::UserTimeZone;
 use crate::ui_helpers::UiHelpers;
 
 #[derive(Debug, PartialEq, Properties)]
@@ -39,8 +40,10 @@ impl Component for DateTimePicker {
     fn view(&self, ctx: &Context<Self>) -> Html {
         let date_format = "%F"; // YYYY-MM-DD
         let time_format = "%R"; // HH:
-        let start_date_string = ctx.props().start_datetime.format(date_format).to_string();
-        let start_time_string = ctx.props().end_datetime.format(time_format).to_string();
+        // Pickers deal in wall clock time for the user's zone.
+        let tz = UserTimeZone::get();
+        let start_date_string = ctx.props().start_datetime.with_timezone(&tz).format(date_format).to_string();
+        let start_time_string = ctx.props().end_datetime.with_timezone(&tz).format(time_format).to_string();
         let oce = ctx.props().onclick.clone();
         let onclick = Callback::from(move |dt| {
             let raw_start_date = UiHelpers::get_value_from_input_by_id("#pickerDateStart")
@@ -55,7 +58,7 @@ impl Component for DateTimePicker {
                     .expect("Bad start time format."),
             );
 
-            oce.emit(new_date.and_time(new_time).and_utc())
+            oce.emit(UserTimeZone::to_utc(&tz, new_date.and_time(new_time)))
         });
 
 
@@ -0,0 +1,68 @@
+use std::collections::HashSet;
+
+use chrono::NaiveDate;
+
+use crate::event_manager::EventManager;
+use crate::schedule_show::Episode;
+use crate::ui_helpers::UiHelpers;
+
+// Rules narrowing down which of the picked episodes actually get scheduled.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EpisodeRules {
+    pub only_unwatched: bool,
+    pub min_runtime: usize, // in Minutes, 0 = Any length.
+    pub aired_after: Option<NaiveDate>,
+    pub range_start: Option<(usize, usize)>, // (season, episode), inclusive.
+    pub range_end: Option<(usize, usize)>,
+}
+
+impl EpisodeRules {
+    // Parses codes like "S02E05" (or "s2e5").
+    pub fn parse_episode_code(code: &str) -> Option<(usize, usize)> {
+        let code = code.trim().to_lowercase();
+        let (season, episode) = code.strip_prefix('s')?.split_once('e')?;
+
+        Some((season.parse().ok()?, episode.parse().ok()?))
+    }
+
+    // Reads the rules out of the rule inputs on the show picker.
+    pub fn from_inputs() -> Self {
+        let text = |id: &str| UiHelpers::get_value_from_input_by_id(id).unwrap_or_default();
+
+        EpisodeRules {
+            only_unwatched: UiHelpers::get_value_from_checkbox_by_id("#rule_only_unwatched")
+                .unwrap_or(false),
+            min_runtime: text("#rule_min_runtime").parse().unwrap_or(0),
+            aired_after: NaiveDate::parse_from_str(&text("#rule_aired_after"), "%Y-%m-%d").ok(),
+            range_start: Self::parse_episode_code(&text("#rule_range_start")),
+            range_end: Self::parse_episode_code(&text("#rule_range_end")),
+        }
+    }
+
+    pub fn allows(&self, ep: &Episode, watched: &HashSet<usize>) -> bool {
+        let code = (ep.season_number, ep.episode_number);
+
+        !(self.only_unwatched && watched.contains(&ep.id))
+            && ep.episode_run_time >= self.min_runtime
+            && self
+                .aired_after
+                .is_none_or(|after| ep.aired_on().is_some_and(|aired| aired > after))
+            && self.range_start.is_none_or(|start| code >= start)
+            && self.range_end.is_none_or(|end| code <= end)
+    }
+
+    pub fn apply(&self, episodes: Vec<Episode>) -> Vec<Episode> {
+        // Episode ids we've already marked watched somewhere on the calendar.
+        let watched: HashSet<usize> = EventManager::create()
+            .events
+            .iter()
+            .filter(|se| se.watched())
+            .filter_map(|se| se.episode.as_ref().map(|ep| ep.id))
+            .collect();
+
+        episodes
+            .into_iter()
+            .filter(|ep| self.allows(ep, &watched))
+            .collect()
+    }
+}
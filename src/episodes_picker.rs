@@ -1,17 +1,16 @@
-use std::ops::Not;
+use std::collections::BTreeSet;
 
 use itertools::Itertools;
-use web_sys::{Event, HtmlElement};
 
 use crate::schedule_show::Season;
 
-use wasm_bindgen::JsCast;
-use wasm_bindgen::UnwrapThrowExt;
 use yew::prelude::*;
 
+// Picked episodes as (season number, episode number) pairs.
+pub type EpisodeSelection = BTreeSet<(usize, usize)>;
+
 #[derive(Clone, PartialEq, Eq)]
 pub enum EpisodePickerTab {
-    // This will kinda do double-duty as state for this component.
     All,
     Some,
     None,
@@ -20,16 +19,78 @@ pub enum EpisodePickerTab {
 pub struct EpisodePicker {
     episode_picker_tab: EpisodePickerTab,
     season: Option<Season>,
+    selected: EpisodeSelection,
+    last_clicked: Option<usize>, // Episode number, anchor for shift-click ranges.
 }
 
 pub enum EpisodePickerMsg {
-    Working,
-    PickerTabChange(Event),
+    PickerTabChange(EpisodePickerTab),
+    Toggle(usize, bool), // Episode number, shift held.
+    SelectAll,
+    SelectNone,
 }
 
-#[derive(Clone, PartialEq, Eq, Properties)]
+#[derive(Clone, PartialEq, Properties)]
 pub struct EpisodePickerProperties {
     pub season: Option<Season>,
+    // Emitted with the season number and that season's full selection whenever it changes.
+    pub onselect: Callback<(usize, EpisodeSelection)>,
+}
+
+impl EpisodePicker {
+    fn season_number(&self) -> usize {
+        self.season.as_ref().map_or(0, |s| s.season_number)
+    }
+
+    fn episode_numbers(&self) -> Vec<usize> {
+        self.season
+            .as_ref()
+            .and_then(|s| s.episodes.as_ref())
+            .map(|eps| eps.iter().map(|e| e.episode_number).collect())
+            .unwrap_or_default()
+    }
+
+    fn select_all(&mut self) {
+        let season_number = self.season_number();
+        self.selected = self
+            .episode_numbers()
+            .into_iter()
+            .map(|ep| (season_number, ep))
+            .collect();
+    }
+
+    // Flips an episode, or with shift held, sets everything between the last click and this one
+    // to whatever the clicked episode ends up as.
+    fn toggle(&mut self, episode_number: usize, shift: bool) {
+        let season_number = self.season_number();
+        let select = !self.selected.contains(&(season_number, episode_number));
+        let episodes = self.episode_numbers();
+        let anchor = self
+            .last_clicked
+            .filter(|_| shift)
+            .and_then(|last| episodes.iter().position(|&ep| ep == last));
+        let clicked = episodes.iter().position(|&ep| ep == episode_number);
+
+        let range = match (anchor, clicked) {
+            (Some(a), Some(c)) => &episodes[a.min(c)..=a.max(c)],
+            _ => std::slice::from_ref(&episode_number),
+        };
+        for &ep in range {
+            if select {
+                self.selected.insert((season_number, ep));
+            } else {
+                self.selected.remove(&(season_number, ep));
+            }
+        }
+
+        self.last_clicked = Some(episode_number);
+    }
+
+    fn notify(&self, ctx: &Context<Self>) {
+        ctx.props()
+            .onselect
+            .emit((self.season_number(), self.selected.clone()));
+    }
 }
 
 impl Component for EpisodePicker {
@@ -37,104 +98,129 @@ impl Component for EpisodePicker {
     type Properties = EpisodePickerProperties;
 
     fn create(ctx: &Context<Self>) -> Self {
-        let episode_picker_tab = EpisodePickerTab::Some;
         let season = ctx.props().season.clone();
-
-        Self {
-            episode_picker_tab,
+        let mut picker = Self {
+            episode_picker_tab: EpisodePickerTab::Some,
             season,
+            selected: EpisodeSelection::new(),
+            last_clicked: None,
+        };
+
+        // Specials are offered, but not picked by default.
+        if picker.season_number() == 0 {
+            picker.episode_picker_tab = EpisodePickerTab::None;
+        } else {
+            picker.select_all();
         }
+        picker.notify(ctx);
+
+        picker
     }
 
-    fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
         match msg {
-            EpisodePickerMsg::PickerTabChange(e) => {
-                let id = e
-                    .target()
-                    .unwrap()
-                    .dyn_into::<HtmlElement>()
-                    .unwrap_throw()
-                    .id();
-                let tab = id.split('_').collect::<Vec<&str>>().pop();
+            EpisodePickerMsg::PickerTabChange(tab) => {
                 match tab {
-                    Some(tab_name) => {
-                        match tab_name.to_uppercase().as_str() {
-                            "ALL" => self.episode_picker_tab = EpisodePickerTab::All,
-                            "SOME" => self.episode_picker_tab = EpisodePickerTab::Some,
-                            "NONE" => self.episode_picker_tab = EpisodePickerTab::None,
-                            _ => {}
-                        }
-                        true
-                    }
-                    _ => {
-                        // Ignore click
-                        false
-                    }
+                    EpisodePickerTab::All => self.select_all(),
+                    EpisodePickerTab::None => self.selected.clear(),
+                    // Start picking from everything, same as a fresh season.
+                    EpisodePickerTab::Some if self.selected.is_empty() => self.select_all(),
+                    EpisodePickerTab::Some => {}
                 }
+                self.episode_picker_tab = tab;
+                self.last_clicked = None;
             }
-            EpisodePickerMsg::Working => false,
+            EpisodePickerMsg::Toggle(episode_number, shift) => self.toggle(episode_number, shift),
+            EpisodePickerMsg::SelectAll => self.select_all(),
+            EpisodePickerMsg::SelectNone => self.selected.clear(),
         }
+        self.notify(ctx);
+
+        true
     }
 
     fn view(&self, ctx: &Context<Self>) -> Html {
         let season = self.season.clone().unwrap();
-        // Supports <=999 seasons? mmk.
-        let season_number = format!("{:002}", season.season_number);
+        let season_number = season.season_number;
         let current_tab = &self.episode_picker_tab;
-        let season_id_base = format!("season_{season_number}");
-        let tab_id_base = format!("{season_id_base}_tab");
-        let tab_click = ctx
-            .link()
-            .callback(move |me: MouseEvent| EpisodePickerMsg::PickerTabChange(Event::from(me)));
+        let episodes = season.episodes.unwrap_or_default();
+        let selected_runtime: usize = episodes
+            .iter()
+            .filter(|e| self.selected.contains(&(season_number, e.episode_number)))
+            .map(|e| e.episode_run_time)
+            .sum();
+        let summary = format!(
+            "{} of {} selected | {} min",
+            self.selected.len(),
+            episodes.len(),
+            selected_runtime
+        );
+
+        let tab = |label: &str, tab: EpisodePickerTab| {
+            let is_active = current_tab == &tab;
+            let onclick = ctx
+                .link()
+                .callback(move |_| EpisodePickerMsg::PickerTabChange(tab.clone()));
+            html! {
+                <li {onclick} class={classes!(is_active.then_some("is-active"))}>
+                    <a><span>{label.to_string()}</span></a>
+                </li>
+            }
+        };
+        let tabs = html! {
+            <ul class="mt-0 ml-0 mb-1">
+                {tab("All", EpisodePickerTab::All)}
+                {tab("Some", EpisodePickerTab::Some)}
+                {tab("None", EpisodePickerTab::None)}
+            </ul>
+        };
 
         match current_tab {
             EpisodePickerTab::Some => {
-                let episodes = season.episodes.unwrap();
                 // I want 3 columns of eps, no matter how many episodes there are per column.
                 // This wont result in the most perfectly even columns, but it works well enough.
-                let chunk_size = (episodes.len() as f32 / 3_f32).ceil();
+                let chunk_size = (episodes.len() as f32 / 3_f32).ceil().max(1_f32);
 
-                let season_chunks: Vec<Html> = (0..=episodes.len())
+                let season_chunks: Vec<Html> = episodes
+                    .iter()
                     .chunks(chunk_size as usize)
                     .into_iter()
-                    .map(|chunks| {
-                        let eps = chunks.into_iter()
-                            .fold(Vec::new(), |mut acc, r| {
-                                if let Some(e) = episodes.get(r) {
-                                        let checkbox_id = format!("{season_id_base}_episode_{:002}", e.episode_number);
-                                        acc.push(html!{
-                                            <div class="field">
-                                            <label class="checkbox">
-                                                <input class="episode_checkbox" id={checkbox_id.to_string()} type="checkbox" checked=true />
-                                                {format!("\u{00A0} {} | {}", e.episode_number, e.name)}
-                                            </label>
-                                            </div>
-                                         });
-                                }
-
-                                acc
+                    .map(|chunk| {
+                        let eps = chunk.map(|e| {
+                            let episode_number = e.episode_number;
+                            let checked = self.selected.contains(&(season_number, episode_number));
+                            let onclick = ctx.link().callback(move |me: MouseEvent| {
+                                EpisodePickerMsg::Toggle(episode_number, me.shift_key())
                             });
-
-                        html!{ <div class="column is-one-third">{for eps }</div> }
+                            html! {
+                                <div class="field">
+                                <label class="checkbox">
+                                    <input class="episode_checkbox" type="checkbox" {checked} {onclick} />
+                                    {format!("\u{00A0} {} | {}", e.episode_number, e.name)}
+                                </label>
+                                </div>
+                            }
+                        });
+
+                        html!{ <div class="column is-one-third">{for eps}</div> }
                     }).collect_vec();
 
                 html! {
                     <>
-                    <div class="tabs is-centered mb-1">
-                        <ul class="mt-0 ml-0 mb-1">
-                            <li onclick={&tab_click}>
-                                <a><span id={format!("{tab_id_base}_all")}>{"All"}</span></a>
-                            </li>
-                            <li onclick={&tab_click} class="is-active">
-                                <a><span id={format!("{tab_id_base}_some")}>{"Some"}</span></a>
-                            </li>
-                            <li onclick={&tab_click}>
-                                <a><span id={format!("{tab_id_base}_none")}>{"None"}</span></a>
-                            </li>
-                        </ul>
-                    </div>
+                    <div class="tabs is-centered mb-1">{tabs}</div>
 
                     <div class="box episode-box">
+                        <div class="level is-mobile mb-1 episode-box-summary">
+                            <div class="level-left">{summary}</div>
+                            <div class="level-right">
+                                <a class="mr-2" onclick={ctx.link().callback(|_| EpisodePickerMsg::SelectAll)}>
+                                    {"Select All"}
+                                </a>
+                                <a onclick={ctx.link().callback(|_| EpisodePickerMsg::SelectNone)}>
+                                    {"Select None"}
+                                </a>
+                            </div>
+                        </div>
                         <div class="columns is-multiline">
                             {for season_chunks}
                         </div>
@@ -142,24 +228,12 @@ impl Component for EpisodePicker {
                     </>
                 }
             }
-            all_or_none => {
-                let is_all = all_or_none == &EpisodePickerTab::All;
+            _ => {
                 html! {
-                    <div class="tabs is-centered">
-                        <ul class="mt-0 ml-0">
-                            <li onclick={&tab_click}
-                                class={classes!(is_all.then_some(Some("is-active")))}>
-                                <a><span id={format!("{tab_id_base}_all")}>{"All"}</span></a>
-                            </li>
-                            <li onclick={&tab_click}>
-                                <a><span id={format!("{tab_id_base}_some")}>{"Some"}</span></a>
-                            </li>
-                            <li onclick={&tab_click}
-                                class={classes!(is_all.not().then_some(Some("is-active")))}>
-                                <a><span id={format!("{tab_id_base}_none")}>{"None"}</span></a>
-                            </li>
-                        </ul>
-                    </div>
+                    <>
+                    <div class="tabs is-centered mb-1">{tabs}</div>
+                    <p class="has-text-centered is-size-7 mb-2">{summary}</p>
+                    </>
                 }
             }
         }
@@ -1,20 +1,40 @@
-use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, TimeZone, Utc};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, Utc};
+use chrono_tz::Tz;
+use gloo::events::EventListener;
+use wasm_bindgen::JsCast;
+use web_sys::{HtmlElement, KeyboardEvent};
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter};
 use std::ops::Sub;
+use std::rc::Rc;
 use uuid::Uuid;
 use wasm_bindgen::prelude::wasm_bindgen;
 use weblog::console_log;
 use yew::prelude::*;
 use crate::event_calendar::EventCalendarMsg::{ChangeDate, ChangeDay, RemoveEvent, RescheduleEvent, ScheduledEventDetails, WatchedEvent};
+use crate::calendars::{CalendarSwitcher, Calendars};
+use crate::catch_up::{missed_events, CatchUpStrategy, MissedEvents};
 use crate::event_details::EventDetails;
-use crate::event_manager::{CsvType, EventManager};
-use crate::events::ScheduledEvent;
+use crate::event_history::EventHistory;
+use crate::event_manager::{CsvType, EventManager, ShowScope};
+use crate::events::{ScheduledEvent, WatchParty, WatchRecord};
+use crate::new_episodes::NewEpisodes;
+use crate::schedule_show::ScheduleShow;
+use crate::share::share_link;
+use crate::show_progress::{ShowProgress, UpNext};
 use crate::search_client::{MediaType};
+use crate::tags::{show_key, tag_chips, Tags};
+use crate::time_grid::TimeGrid;
+use crate::time_zone::UserTimeZone;
 use crate::ui_helpers::UiHelpers;
+use crate::Route;
+use yew_router::prelude::*;
+use itertools::Itertools;
 
 #[wasm_bindgen(module = "/js/helpers.js")]
 extern "C" {
     #[wasm_bindgen(js_name = export_file)]
-    fn export_file(filename: &str, data: &str, data_type: &str);
+    pub(crate) fn export_file(filename: &str, data: &str, data_type: &str);
 }
 
 // pub struct EventModal {
@@ -34,10 +54,50 @@ pub trait CalendarSchedulableEvent {
     fn duration(&self) -> usize; // in minutes
 }
 
+// Where a calendar showing the given day in the given view lives.
+pub fn calendar_route(view: CalendarView, day: NaiveDate) -> Route {
+    match view {
+        CalendarView::Month => Route::CalendarMonthDay {
+            year: day.year(),
+            month: day.month(),
+            day: day.day(),
+        },
+        CalendarView::Week => Route::CalendarWeek { date: day },
+        CalendarView::Day => Route::CalendarDay { date: day },
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CalendarView {
+    #[default]
+    Month,
+    Week,
+    Day,
+}
+
+impl Display for CalendarView {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let view = match self {
+            CalendarView::Month => "month",
+            CalendarView::Week => "week",
+            CalendarView::Day => "day",
+        };
+        write!(f, "{view}")
+    }
+}
+
 pub struct EventCalendar {
     active_day: DateTime<Utc>,
     active_month: DateTime<Utc>,
+    view: CalendarView,
     active_event: Option<ScheduledEvent>,
+    time_zone: Tz,
+    redistributing: Option<(usize, Vec<ScheduledEvent>)>, // Show id and the events being laid out again.
+    select_mode: bool,
+    selection: HashSet<Uuid>,
+    share_link: Option<String>,
+    tag_filter: Option<String>, // Only events with this tag are shown and exported.
+    _keydown: EventListener, // Undo/redo shortcuts, dropped along with the calendar.
 }
 
 pub enum EventCalendarMsg {
@@ -45,9 +105,33 @@ pub enum EventCalendarMsg {
     ChangeDay(DateTime<Utc>),
     ScheduledEventDetails(Option<ScheduledEvent>),
     RemoveEvent(Uuid),
-    WatchedEvent(Uuid),
+    WatchedEvent(Uuid, WatchRecord),
+    UnwatchEvent(Uuid),
     RescheduleEvent(Uuid, DateTime<Utc>),
+    MoveEventToDay(Uuid, NaiveDate),
+    MoveEventToCalendar(Uuid, Uuid),
+    SetParty(Uuid, Option<WatchParty>),
+    SetTags(Uuid, Vec<String>, Vec<String>), // Event id, event tags, show tags.
+    SetTagColor(String, String),
+    FilterTag(Option<String>),
+    ShiftShow(usize, ShowScope, i64),
+    RedistributeShow(usize, ShowScope),
+    RedistributeClosed,
+    ToggleSelectMode,
+    SelectDay,
+    ClearSelection,
+    BulkRemove,
+    BulkWatched(bool),
+    BulkShift,
+    BulkRemoveShows,
+    CatchUp(CatchUpStrategy),
+    Share,
+    ShareClosed,
+    Undo,
+    Redo,
     ExportCsv,
+    ExportIcal,
+    Refresh,
 }
 
 #[derive(Clone, PartialEq, Eq, Properties)]
@@ -55,10 +139,15 @@ pub struct EventCalendarProperties {
     #[prop_or(Utc::now())]
     pub date: DateTime<Utc>,
     #[prop_or_default]
+    pub view: CalendarView,
+    // Opens the event's details on top of the calendar, e.g. from /event/{uuid}.
+    #[prop_or_default]
+    pub event: Option<Uuid>,
+    #[prop_or_default]
     pub events: Vec<String>,
 }
 
-fn get_calendar_cells(date: &DateTime<Utc>) -> Vec<Option<NaiveDate>> {
+pub(crate) fn get_calendar_cells(date: &NaiveDate) -> Vec<Option<NaiveDate>> {
     let mut cells: Vec<Option<NaiveDate>> = vec![];
     let year = date.year();
     let month = date.month();
@@ -85,18 +174,66 @@ fn get_calendar_cells(date: &DateTime<Utc>) -> Vec<Option<NaiveDate>> {
     cells
 }
 
+// Monday through Sunday of the week holding the given date, matching the month grid.
+fn get_week_days(date: &NaiveDate) -> Vec<NaiveDate> {
+    let monday = *date - Duration::days(date.weekday().num_days_from_monday() as i64);
+    (0..7).map(|offset| monday + Duration::days(offset)).collect()
+}
+
 #[derive(Clone, PartialEq, Properties)]
 pub struct EventItemProps {
     pub scheduled_event: ScheduledEvent,
+    pub time_zone: Tz,
     pub onclick: Callback<Option<ScheduledEvent>>,
+    // Some when the calendar is in select mode, holding whether this event is picked.
+    #[prop_or_default]
+    pub selected: Option<bool>,
+    // Moves the event to another day, keeping its time of day.
+    #[prop_or_default]
+    pub onmove: Callback<(Uuid, NaiveDate)>,
+    // The event's calendar color, when more than one calendar is showing.
+    #[prop_or_default]
+    pub color: Option<String>,
+    // Loaded once by whoever's listing events rather than by every item.
+    pub tags: Rc<Tags>,
 }
 
 #[function_component(EventItem)]
 pub fn event_item(props: &EventItemProps) -> Html {
     let se = props.scheduled_event.clone();
+    let start = se.scheduled_date.with_timezone(&props.time_zone);
     let out = props.scheduled_event.clone();
     let oce = props.onclick.clone();
-    let onclick = Callback::from(move |_| oce.emit(Some(out.clone())));
+    let onclick = {
+        let out = out.clone();
+        let oce = oce.clone();
+        Callback::from(move |_| oce.emit(Some(out.clone())))
+    };
+    let uuid = se.uuid;
+    let ondragstart = Callback::from(move |e: DragEvent| {
+        if let Some(dt) = e.data_transfer() {
+            let _ = dt.set_data("text/plain", &uuid.to_string());
+            dt.set_effect_allowed("move");
+        }
+    });
+    // Keyboard alternative to dragging: arrows move a day (left/right) or a week (up/down).
+    let ome = props.onmove.clone();
+    let local_day = start.date_naive();
+    let onkeydown = Callback::from(move |e: KeyboardEvent| {
+        let days = match e.key().as_str() {
+            "ArrowLeft" => -1,
+            "ArrowRight" => 1,
+            "ArrowUp" => -7,
+            "ArrowDown" => 7,
+            "Enter" => {
+                oce.emit(Some(out.clone()));
+                return;
+            }
+            _ => return,
+        };
+        e.prevent_default();
+        ome.emit((uuid, local_day + Duration::days(days)));
+    });
     // let oce = Callback::from(| _: MouseEvent| {
     //         EventCalendarMsg::EventDetails("Blap".parse().unwrap());
     // });
@@ -107,7 +244,7 @@ pub fn event_item(props: &EventItemProps) -> Html {
         MediaType::tv => {
             // [ICON] 16:30 | The Office - The Dundies
             let t = format! {" {} | {} - {}",
-            se.scheduled_date.format("%R"),
+            start.format("%R"),
             se.episode.as_ref().unwrap().show_name,
             se.episode.as_ref().unwrap().name };
             let i = "gg-tv".to_string();
@@ -117,7 +254,7 @@ pub fn event_item(props: &EventItemProps) -> Html {
         MediaType::movie => {
             // [ICON] 16:30 | Ghostbusters
             let t = format! {" {} | {}",
-            se.scheduled_date.format("%R"),
+            start.format("%R"),
             se.movie.as_ref().unwrap().show_name };
             let i = "gg-film".to_string();
 
@@ -129,37 +266,145 @@ pub fn event_item(props: &EventItemProps) -> Html {
     };
 
     let mut class = classes!("panel-block", "schedule-item");
-    if se.watched {
+    if se.watched() {
         class.push("event-watched");
     }
+    if se.rewatched() {
+        class.push("event-rewatched");
+    }
+    if props.selected == Some(true) {
+        class.push("is-selected");
+    }
+    let chips = tag_chips(&props.tags, &props.tags.event_tags(&se));
+    let icon_style = props.tags.event_color(&se).map(|color| format!("color: {color};"));
+    // Watch party guests as initials, hover for the full list and where it's at.
+    let party = se.party.as_ref().map(|party| {
+        let title = match &party.location {
+            Some(location) => format!("Watch party with {} at {location}", party.participants.join(", ")),
+            None => format!("Watch party with {}", party.participants.join(", ")),
+        };
+        let badges = party
+            .participants
+            .iter()
+            .map(|p| html! { <span class="tag is-rounded is-warning party-badge">{WatchParty::initials(p)}</span> })
+            .collect::<Html>();
+        html! {
+            <span class="party-badges" {title}>
+                <i class="gg-user-list" aria-hidden="true"></i>
+                {badges}
+            </span>
+        }
+    });
+
+    let style = props
+        .color
+        .as_ref()
+        .map(|color| format!("border-left: 4px solid {color};"));
 
     html! {
-        <a {class} {onclick}>
-            <span class="panel-icon">
+        <a {class} {style} {onclick} {onkeydown} {ondragstart} draggable="true" tabindex="0"
+            title="Drag to another day, or focus and use the arrow keys to move it">
+            if let Some(selected) = props.selected {
+                <input class="mr-1" type="checkbox" checked={selected} />
+            }
+            <span class="panel-icon" style={icon_style}>
                 <i class={icon} aria-hidden="true"></i>
             </span>
             {text}
+            {party}
+            <span class="event-tags">{chips}</span>
         </a>
     }
 }
 
-// TODO: Pretty sure the TimeZone management on this component is wonk.
-// TODO: Implement a configuration setting for what TimeZone to use by default for display.
+impl EventCalendar {
+    // The URL for what's on screen, so a reload or shared link lands back here.
+    fn route(&self) -> Route {
+        match &self.active_event {
+            Some(se) => Route::Event { id: se.uuid },
+            None => calendar_route(self.view, self.active_day.with_timezone(&self.time_zone).date_naive()),
+        }
+    }
+
+    // What's worth a browser history entry: the view, the month and the open event.
+    // Moving between days or selecting events just updates the address in place.
+    fn history_key(&self) -> (CalendarView, i32, u32, Option<Uuid>) {
+        let day = self.active_day.with_timezone(&self.time_zone);
+        (self.view, day.year(), day.month(), self.active_event.as_ref().map(|se| se.uuid))
+    }
+
+    fn find_event(event_id: Option<Uuid>) -> Option<ScheduledEvent> {
+        let event_id = event_id?;
+        EventManager::create().events.into_iter().find(|se| se.uuid == event_id)
+    }
+}
+
+// Everything is stored as UTC, the calendar itself is laid out in the user's configured zone.
 impl Component for EventCalendar {
     type Message = EventCalendarMsg;
     type Properties = EventCalendarProperties;
 
     fn create(ctx: &Context<Self>) -> Self {
         let current_date = ctx.props().date;
+        // Ctrl+Z undoes, Ctrl+Shift+Z or Ctrl+Y redoes. Text fields keep their own undo.
+        let link = ctx.link().clone();
+        let keydown = EventListener::new(&gloo_utils::document(), "keydown", move |e| {
+            let Some(ke) = e.dyn_ref::<KeyboardEvent>() else {
+                return;
+            };
+            let in_text_field = e
+                .target()
+                .and_then(|t| t.dyn_into::<HtmlElement>().ok())
+                .is_some_and(|el| matches!(el.tag_name().as_str(), "INPUT" | "TEXTAREA" | "SELECT"));
+            if !(ke.ctrl_key() || ke.meta_key()) || in_text_field {
+                return;
+            }
+            let msg = match ke.key().to_lowercase().as_str() {
+                "z" if ke.shift_key() => EventCalendarMsg::Redo,
+                "z" => EventCalendarMsg::Undo,
+                "y" => EventCalendarMsg::Redo,
+                _ => return,
+            };
+            ke.prevent_default();
+            link.send_message(msg);
+        });
+
+        let time_zone = UserTimeZone::get();
+        CatchUpStrategy::apply_policy_on_load(&time_zone);
+
         Self {
             active_day: current_date,
             active_month: current_date,
-            active_event: None,
+            view: ctx.props().view,
+            active_event: Self::find_event(ctx.props().event),
+            time_zone,
+            redistributing: None,
+            select_mode: false,
+            selection: HashSet::new(),
+            share_link: None,
+            tag_filter: None,
+            _keydown: keydown,
         }
     }
 
-    fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
-        match msg {
+    // Route changes (e.g. /calendar/week/2026-10-12) reuse the component, so pick up the new props.
+    fn changed(&mut self, ctx: &Context<Self>, _old_props: &Self::Properties) -> bool {
+        let props = ctx.props();
+        self.active_event = Self::find_event(props.event);
+        // An event opens over whatever is already showing.
+        if props.event.is_none() {
+            self.active_day = props.date;
+            self.active_month = props.date;
+            self.view = props.view;
+        }
+
+        true
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        let before = self.route();
+        let before_key = self.history_key();
+        let render = match msg {
             ChangeDate(new_date) => {
                 self.active_month = new_date;
                 // Today should pull up the current day if you're in the same month already.
@@ -171,12 +416,22 @@ impl Component for EventCalendar {
             }
             ChangeDay(day) => {
                 self.active_day = day;
+                // Stepping through weeks or days can walk into another month.
+                self.active_month = day;
 
                 true
             }
             ScheduledEventDetails(scheduled_event) => {
                 // console_log!(format!("Showing details for: {scheduled_event:?}"));
-                self.active_event = scheduled_event;
+                match scheduled_event {
+                    // Clicks pick events instead of opening them while selecting.
+                    Some(se) if self.select_mode => {
+                        if !self.selection.remove(&se.uuid) {
+                            self.selection.insert(se.uuid);
+                        }
+                    }
+                    _ => self.active_event = scheduled_event,
+                }
                 true
             }
             RemoveEvent(event_id) => {
@@ -186,10 +441,18 @@ impl Component for EventCalendar {
 
                 true
             }
-            WatchedEvent(event_id) => {
+            WatchedEvent(event_id, record) => {
                 let mut em = EventManager::create();
-                let _ = em.watched_event(event_id);
-                self.active_event = None;
+                let _ = em.log_watch(event_id, record);
+                // Stay open so the new entry shows up in the log.
+                self.active_event = em.events.into_iter().find(|se| se.uuid == event_id);
+
+                true
+            }
+            EventCalendarMsg::UnwatchEvent(event_id) => {
+                let mut em = EventManager::create();
+                let _ = em.unlog_watch(event_id);
+                self.active_event = em.events.into_iter().find(|se| se.uuid == event_id);
 
                 true
             }
@@ -202,9 +465,209 @@ impl Component for EventCalendar {
 
                 true
             }
-            ExportCsv => {
+            EventCalendarMsg::MoveEventToDay(event_id, day) => {
+                let tz = self.time_zone;
+                let mut em = EventManager::create();
+                let Some(se) = em.events.iter().find(|se| se.uuid == event_id) else {
+                    return false;
+                };
+                let local = se.scheduled_date.with_timezone(&tz);
+                if local.date_naive() == day {
+                    return false;
+                }
+                let datetime = UserTimeZone::to_utc(&tz, day.and_time(local.time()));
+                console_log!(format!("Moving: {event_id}\nTo:{datetime}"));
+
+                let _ = em.reschedule_event(event_id, datetime);
+                // Follow the event so it stays in view.
+                self.active_day = datetime;
+                self.active_month = datetime;
+
+                true
+            }
+            EventCalendarMsg::MoveEventToCalendar(event_id, calendar) => {
+                let mut em = EventManager::create();
+                let _ = em.move_to_calendar(&[event_id], calendar);
+                self.active_event = em.events.into_iter().find(|se| se.uuid == event_id);
+
+                true
+            }
+            EventCalendarMsg::SetParty(event_id, party) => {
+                let mut em = EventManager::create();
+                let _ = em.set_party(event_id, party);
+                self.active_event = em.events.into_iter().find(|se| se.uuid == event_id);
+
+                true
+            }
+            EventCalendarMsg::SetTags(event_id, event_tags, show_tags) => {
+                let mut em = EventManager::create();
+                let _ = em.set_tags(event_id, event_tags);
+                self.active_event = em.events.into_iter().find(|se| se.uuid == event_id);
+                if let Some(key) = self.active_event.as_ref().and_then(show_key) {
+                    let mut tags = Tags::load();
+                    tags.set_show_tags(key, show_tags);
+                    if let Err(e) = tags.store() {
+                        console_log!(format!("BYNGER - Show Tags Failed - {e}"));
+                    }
+                }
+
+                true
+            }
+            EventCalendarMsg::SetTagColor(tag, color) => {
+                let mut tags = Tags::load();
+                tags.set_color(&tag, color);
+                if let Err(e) = tags.store() {
+                    console_log!(format!("BYNGER - Tag Color Failed - {e}"));
+                }
+
+                true
+            }
+            EventCalendarMsg::FilterTag(tag) => {
+                self.tag_filter = tag;
+
+                true
+            }
+            EventCalendarMsg::ShiftShow(show_id, scope, days) => {
+                console_log!(format!("Shifting show {show_id} by {days} days"));
+
                 let mut em = EventManager::create();
-                if let Ok(csv) = em.events_as_csv(CsvType::GCAL) {
+                let _ = em.shift_show_events(show_id, scope, days);
+                self.active_event = None;
+
+                true
+            }
+            EventCalendarMsg::RedistributeShow(show_id, scope) => {
+                let remaining = EventManager::create().show_events(show_id, scope);
+                self.active_event = None;
+                if !remaining.is_empty() {
+                    self.redistributing = Some((show_id, remaining));
+                }
+
+                true
+            }
+            EventCalendarMsg::RedistributeClosed => {
+                self.redistributing = None;
+
+                true
+            }
+            EventCalendarMsg::ToggleSelectMode => {
+                self.select_mode = !self.select_mode;
+                self.selection.clear();
+
+                true
+            }
+            EventCalendarMsg::SelectDay => {
+                let tz = self.time_zone;
+                let day = self.active_day.with_timezone(&tz).date_naive();
+                let calendars = Calendars::load();
+                self.selection.extend(
+                    EventManager::create()
+                        .events
+                        .iter()
+                        .filter(|se| calendars.shows(se))
+                        .filter(|se| se.scheduled_date.with_timezone(&tz).date_naive() == day)
+                        .map(|se| se.uuid),
+                );
+
+                true
+            }
+            EventCalendarMsg::ClearSelection => {
+                self.selection.clear();
+
+                true
+            }
+            EventCalendarMsg::BulkRemove => {
+                let selected = self.selection.drain().collect::<Vec<Uuid>>();
+                let _ = EventManager::create().remove_events(&selected);
+
+                true
+            }
+            EventCalendarMsg::BulkWatched(watched) => {
+                let selected = self.selection.iter().copied().collect::<Vec<Uuid>>();
+                let _ = EventManager::create().set_watched(&selected, watched);
+
+                true
+            }
+            EventCalendarMsg::BulkShift => {
+                let days = UiHelpers::get_value_from_input_by_id("#bulkShiftDays")
+                    .and_then(|raw| raw.parse::<i64>().ok())
+                    .unwrap_or(0);
+                if days == 0 {
+                    return false;
+                }
+                let selected = self.selection.iter().copied().collect::<Vec<Uuid>>();
+                let _ = EventManager::create().shift_events(&selected, days);
+
+                true
+            }
+            EventCalendarMsg::BulkRemoveShows => {
+                let mut em = EventManager::create();
+                let show_ids = em
+                    .events
+                    .iter()
+                    .filter(|se| self.selection.contains(&se.uuid))
+                    .filter_map(|se| se.episode.as_ref().map(|ep| ep.show_id))
+                    .collect::<Vec<usize>>();
+                let _ = em.remove_show_events(&show_ids);
+                self.selection.clear();
+
+                true
+            }
+            EventCalendarMsg::CatchUp(strategy) => {
+                match EventManager::create().catch_up(strategy, &self.time_zone, Utc::now()) {
+                    Ok(moved) => console_log!(format!("BYNGER - Caught up {moved} events")),
+                    Err(e) => console_log!(format!("BYNGER - Catch Up Failed - {e}")),
+                }
+
+                true
+            }
+            EventCalendarMsg::Share => {
+                // The selection if there is one, otherwise everything still to come.
+                let now = Utc::now();
+                let calendars = Calendars::load();
+                let shared = EventManager::create()
+                    .events
+                    .into_iter()
+                    .filter(|se| match self.selection.is_empty() {
+                        true => se.scheduled_date >= now && calendars.shows(se),
+                        false => self.selection.contains(&se.uuid),
+                    })
+                    .collect::<Vec<ScheduledEvent>>();
+                match share_link(&shared) {
+                    Ok(link) => self.share_link = Some(link),
+                    Err(e) => console_log!(format!("BYNGER - Share Failed - {e}")),
+                }
+
+                true
+            }
+            EventCalendarMsg::ShareClosed => {
+                self.share_link = None;
+
+                true
+            }
+            EventCalendarMsg::Undo => {
+                match EventManager::create().undo() {
+                    Ok(Some(label)) => console_log!(format!("BYNGER - Undid: {label}")),
+                    Ok(None) => return false,
+                    Err(e) => console_log!(format!("BYNGER - Undo Failed - {e}")),
+                }
+                self.active_event = None;
+
+                true
+            }
+            EventCalendarMsg::Redo => {
+                match EventManager::create().redo() {
+                    Ok(Some(label)) => console_log!(format!("BYNGER - Redid: {label}")),
+                    Ok(None) => return false,
+                    Err(e) => console_log!(format!("BYNGER - Redo Failed - {e}")),
+                }
+                self.active_event = None;
+
+                true
+            }
+            EventCalendarMsg::ExportCsv => {
+                let mut em = EventManager::create();
+                if let Ok(csv) = em.events_as_csv(CsvType::GCAL, self.tag_filter.as_deref()) {
                     // Push our CSV to the client as it's own file.
                     let now = Utc::now().format("%Y%m%d_%H%M%S");
                     export_file(
@@ -215,25 +678,88 @@ impl Component for EventCalendar {
                 }
                 false
             }
+            EventCalendarMsg::Refresh => true,
+            EventCalendarMsg::ExportIcal => {
+                let mut em = EventManager::create();
+                if let Ok(ics) = em.events_as_csv(CsvType::ICAL, self.tag_filter.as_deref()) {
+                    let now = Utc::now().format("%Y%m%d_%H%M%S");
+                    export_file(
+                        format!("bynger_event_export_{now}.ics").as_str(),
+                        &ics,
+                        "text/calendar",
+                    )
+                }
+                false
+            }
+        };
+
+        // Keep the address bar and browser history in step with the calendar.
+        let after = self.route();
+        if after != before {
+            if let Some(navigator) = ctx.link().navigator() {
+                if self.history_key() != before_key {
+                    navigator.push(&after);
+                } else {
+                    navigator.replace(&after);
+                }
+            }
         }
+
+        render
     }
 
     fn view(&self, ctx: &Context<Self>) -> Html {
         let em = EventManager::create();
-        let day = self.active_day;
-        let date = self.active_month;
+        // Only the calendars being looked at, the rest are still there for catching up etc.
+        let calendars = Rc::new(Calendars::load());
+        let tags = Rc::new(Tags::load());
+        let tagged = |se: &&ScheduledEvent| self.tag_filter.as_ref().is_none_or(|tag| tags.has_tag(se, tag));
+        let visible = em
+            .events
+            .iter()
+            .filter(|se| calendars.shows(se))
+            .filter(tagged)
+            .cloned()
+            .collect::<Vec<ScheduledEvent>>();
+        let tz = self.time_zone;
+        let day = self.active_day.with_timezone(&tz);
+        let date = self.active_month.with_timezone(&tz);
         let dn = day.date_naive();
+        let local_date = move |se: &ScheduledEvent| se.scheduled_date.with_timezone(&tz).date_naive();
         let onclick_event = ctx.link().callback(ScheduledEventDetails);
         let onclick_event_close = ctx.link().callback(move |_| ScheduledEventDetails(None));
         let onclick_event_remove = ctx.link().callback(RemoveEvent);
-        let onclick_event_watched = ctx.link().callback(WatchedEvent);
+        let onclick_event_watched = ctx.link().callback(|(uuid, record)| WatchedEvent(uuid, record));
+        let onclick_event_unwatched = ctx.link().callback(EventCalendarMsg::UnwatchEvent);
         let onclick_event_reschedule = ctx.link().callback(move |(uuid, dt)| RescheduleEvent(uuid, dt));
+        let onmove_event = ctx
+            .link()
+            .callback(|(uuid, day)| EventCalendarMsg::MoveEventToDay(uuid, day));
+        let onshiftshow = ctx
+            .link()
+            .callback(|(show_id, scope, days)| EventCalendarMsg::ShiftShow(show_id, scope, days));
+        let onredistributeshow = ctx
+            .link()
+            .callback(|(show_id, scope)| EventCalendarMsg::RedistributeShow(show_id, scope));
 
         let onexport = ctx.link().callback(|_| EventCalendarMsg::ExportCsv);
+        let onscheduled = ctx.link().callback(|_| EventCalendarMsg::Refresh);
+        let onexport_ical = ctx.link().callback(|_| EventCalendarMsg::ExportIcal);
+        let view = self.view;
         let chevron_click = ctx.link().callback(move |me: MouseEvent| {
-            let mut out_date = date;
+            let mut out_date = date.with_timezone(&Utc);
             if let Some(elem_id) = UiHelpers::get_id_from_event_elem(Event::from(me)) {
                 if let Some(direction) = elem_id.strip_prefix("cal_month_") {
+                    // Week and day views page by their own span instead of by month.
+                    let step = match view {
+                        CalendarView::Month => 0,
+                        CalendarView::Week => 7,
+                        CalendarView::Day => 1,
+                    };
+                    if step != 0 && direction != "curr" {
+                        let days = if direction == "next" { step } else { -step };
+                        return ChangeDay(day.with_timezone(&Utc) + Duration::days(days));
+                    }
                     let mut year = date.year();
                     let mut month = date.month();
                     match direction {
@@ -245,18 +771,18 @@ impl Component for EventCalendar {
                                 month += 1;
                             }
                             let next_month = NaiveDate::from_ymd_opt(year, month, 1).unwrap();
-                            out_date = DateTime::from_utc(
+                            out_date = UserTimeZone::to_utc(
+                                &tz,
                                 next_month.and_time(NaiveTime::from_hms_opt(0, 0, 1).unwrap()),
-                                Utc,
                             );
                         }
                         "prev" => {
                             let prev_month = NaiveDate::from_ymd_opt(year, month, 1)
                                 .unwrap()
                                 .sub(Duration::days(1));
-                            out_date = DateTime::from_utc(
+                            out_date = UserTimeZone::to_utc(
+                                &tz,
                                 prev_month.and_time(NaiveTime::from_hms_opt(0, 0, 1).unwrap()),
-                                Utc,
                             );
                         }
                         "curr" => {
@@ -270,30 +796,29 @@ impl Component for EventCalendar {
         });
 
         // Must be mutable to sort after collection.
-        let mut day_events: Vec<&ScheduledEvent> = em
-            .events
+        let mut day_events: Vec<&ScheduledEvent> = visible
             .iter()
-            .filter(|se| se.scheduled_date.date_naive() == dn)
+            .filter(|se| local_date(se) == dn)
             .collect();
         // Sort our day's events by time...
         day_events.sort_by(|a, b| a.scheduled_date.cmp(&b.scheduled_date));
 
-        let cells = get_calendar_cells(&date);
+        let cells = get_calendar_cells(&date.date_naive());
         let cell_id_format = "%Y_%m_%d";
         let day_click = ctx.link().callback(move |me: MouseEvent| {
-            let mut out = day;
+            let mut out = day.with_timezone(&Utc);
             if let Some(elem_id) = UiHelpers::get_id_from_event_elem(Event::from(me)) {
                 let id_split: Vec<&str> = elem_id.split('_').collect();
-                out = Utc
-                    .with_ymd_and_hms(
-                        id_split[0].parse::<i32>().unwrap(),
-                        id_split[1].parse::<u32>().unwrap(),
-                        id_split[2].parse::<u32>().unwrap(),
-                        0,
-                        0,
-                        1,
-                    )
-                    .unwrap();
+                let clicked = NaiveDate::from_ymd_opt(
+                    id_split[0].parse::<i32>().unwrap(),
+                    id_split[1].parse::<u32>().unwrap(),
+                    id_split[2].parse::<u32>().unwrap(),
+                )
+                .unwrap();
+                out = UserTimeZone::to_utc(
+                    &tz,
+                    clicked.and_time(NaiveTime::from_hms_opt(0, 0, 1).unwrap()),
+                );
             }
 
             ChangeDay(out)
@@ -304,21 +829,39 @@ impl Component for EventCalendar {
                 None => html! {<td></td>},
                 Some(d) => {
                     let day_id = d.format(cell_id_format).to_string();
-                    let events: Vec<&ScheduledEvent> = em
-                        .events
+                    let events: Vec<&ScheduledEvent> = visible
                         .iter()
-                        .filter(|se| se.scheduled_date.date_naive() == d)
+                        .filter(|se| local_date(se) == d)
                         .collect();
+                    // One dot per calendar with something on, when overlaying.
+                    let dots = events
+                        .iter()
+                        .filter_map(|se| calendars.color(se))
+                        .unique()
+                        .map(|color| html! {
+                            <span id={day_id.clone()} class="calendar-dot" style={format!("background-color: {color};")}></span>
+                        })
+                        .collect::<Html>();
+                    let ondragover = Callback::from(|e: DragEvent| e.prevent_default());
+                    let ondrop = ctx.link().batch_callback(move |e: DragEvent| {
+                        e.prevent_default();
+                        e.data_transfer()
+                            .and_then(|dt| dt.get_data("text/plain").ok())
+                            .and_then(|raw| Uuid::parse_str(&raw).ok())
+                            .map(|uuid| EventCalendarMsg::MoveEventToDay(uuid, d))
+                    });
                     html! {
                         // Even though the onclick is on the TD, nested elements trigger it and fail
                         // to pick up the ID properly. Hackily adding the ID to all the elements
                         // resolves this. Not ideal, but works without breaking anything.
                         <td class="day-link is-clickable"
-                            id={day_id.clone()} onclick={&day_click}
+                            id={day_id.clone()} onclick={&day_click} {ondragover} {ondrop}
                             title={format!("{} Events Scheduled", events.len())}>
                             <div id={day_id.clone()} class="is-inline-block">
                                 {d.format("%d")}
-                                if !events.is_empty() {
+                                if calendars.overlay {
+                                    <span id={day_id.clone()} class="calendar-dots">{dots}</span>
+                                } else if !events.is_empty() {
                                     <img id={day_id.clone()} class="events-tag" />
                                 }
                             </div>
@@ -327,6 +870,95 @@ impl Component for EventCalendar {
                 }
             }
         };
+        let link = ctx.link();
+        let history = EventHistory::load();
+        let undo_title = history.undo_label().map_or(String::from("Nothing to undo"), |l| format!("Undo: {l} (Ctrl+Z)"));
+        let redo_title = history.redo_label().map_or(String::from("Nothing to redo"), |l| format!("Redo: {l} (Ctrl+Shift+Z)"));
+        let bulk_actions = if self.select_mode {
+            let count = self.selection.len();
+            html! {
+                <div class="card-footer-item bulk-actions">
+                    <div class="buttons are-small is-centered mb-1">
+                        <button class="button" onclick={link.callback(|_| EventCalendarMsg::SelectDay)}>{"Select Day"}</button>
+                        <button class="button" onclick={link.callback(|_| EventCalendarMsg::ClearSelection)}>{"Clear"}</button>
+                        <button class="button is-light" onclick={link.callback(|_| EventCalendarMsg::ToggleSelectMode)}>{"Done"}</button>
+                    </div>
+                    <p class="is-size-7 mb-1">{format!("{count} selected")}</p>
+                    <div class="buttons are-small is-centered mb-1">
+                        <button class="button is-info" disabled={count == 0}
+                            onclick={link.callback(|_| EventCalendarMsg::BulkWatched(true))}>{"Watched"}</button>
+                        <button class="button is-info is-outlined" disabled={count == 0}
+                            onclick={link.callback(|_| EventCalendarMsg::BulkWatched(false))}>{"Unwatched"}</button>
+                        <button class="button is-danger" disabled={count == 0}
+                            onclick={link.callback(|_| EventCalendarMsg::BulkRemove)}>{"Remove"}</button>
+                        <button class="button is-danger is-outlined" disabled={count == 0}
+                            title="Remove every event of the selected events' shows"
+                            onclick={link.callback(|_| EventCalendarMsg::BulkRemoveShows)}>{"Remove Shows"}</button>
+                    </div>
+                    <div class="field has-addons has-addons-centered">
+                        <p class="control">
+                            <input class="input is-small shift-days" id="bulkShiftDays" type="number" value="1" />
+                        </p>
+                        <p class="control">
+                            <button class="button is-small is-warning" disabled={count == 0}
+                                onclick={link.callback(|_| EventCalendarMsg::BulkShift)}>{"Shift Days"}</button>
+                        </p>
+                    </div>
+                </div>
+            }
+        } else {
+            html! {
+                <a class="card-footer-item" onclick={link.callback(|_| EventCalendarMsg::ToggleSelectMode)}>
+                    {"select"}
+                </a>
+            }
+        };
+
+        let view_link = |v: CalendarView| {
+            let classes = if v == self.view { "button is-selected is-info" } else { "button" };
+            html! {
+                <Link<Route> to={calendar_route(v, dn)} {classes}>
+                    {v.to_string()}
+                </Link<Route>>
+            }
+        };
+        let grid_days = match self.view {
+            CalendarView::Month => vec![],
+            CalendarView::Week => get_week_days(&dn),
+            CalendarView::Day => vec![dn],
+        };
+        let (heading, title) = match (grid_days.first(), grid_days.last()) {
+            (Some(first), Some(last)) if first != last => (
+                first.format("%Y").to_string(),
+                format!("{} - {}", first.format("%b %d"), last.format("%b %d")),
+            ),
+            (Some(only), _) => (only.format("%B %Y").to_string(), only.format("%A %d").to_string()),
+            _ => (date.format("%Y").to_string(), date.format("%B").to_string()),
+        };
+        // Events starting the day before the grid can run past midnight into it.
+        let grid_events = match (grid_days.first(), grid_days.last()) {
+            (Some(&first), Some(&last)) => visible
+                .iter()
+                .filter(|se| (first - Duration::days(1)..=last).contains(&local_date(se)))
+                .cloned()
+                .collect::<Vec<ScheduledEvent>>(),
+            _ => vec![],
+        };
+
+        let grid_colors = grid_events
+            .iter()
+            .filter_map(|se| calendars.color(se).map(|color| (se.uuid, color)))
+            .collect::<HashMap<Uuid, String>>();
+
+        let all_tags = tags.all_tags(&em.events);
+        let ontagfilter = link.callback(|e: Event| {
+            EventCalendarMsg::FilterTag(UiHelpers::get_value_from_event(&e).filter(|tag| !tag.is_empty()))
+        });
+
+        let progress = ShowProgress::from_events(&visible);
+        let missed = missed_events(&em.events, Utc::now()).len();
+        let oncatchup = ctx.link().callback(EventCalendarMsg::CatchUp);
+
         let weeks = cells
             .chunks(7)
             .map(|week| {
@@ -344,6 +976,16 @@ impl Component for EventCalendar {
 
         html! {
             <>
+            <NewEpisodes {onscheduled} />
+            <MissedEvents {missed} {oncatchup} />
+            if let Some(share) = &self.share_link {
+                <div class="notification is-info share-link">
+                    <button class="delete" aria-label="close"
+                        onclick={link.callback(|_| EventCalendarMsg::ShareClosed)}></button>
+                    <p class="mb-1">{"Anyone with this link can see these events and add them to their calendar:"}</p>
+                    <input class="input is-small" type="text" readonly=true value={share.clone()} />
+                </div>
+            }
             <div class="is-centered box calendar-container">
                 <div class="columns">
                     <div class="column calendar-left">
@@ -372,7 +1014,12 @@ impl Component for EventCalendar {
                                             html!{
                                                 <EventItem
                                                 scheduled_event={ev.clone()}
+                                                time_zone={tz}
                                                 onclick={onclick_event.clone()}
+                                                onmove={onmove_event.clone()}
+                                                selected={self.select_mode.then(|| self.selection.contains(&ev.uuid))}
+                                                color={calendars.color(ev)}
+                                                tags={tags.clone()}
                                             />
                                             }
                                         }).collect::<Html>()
@@ -380,11 +1027,13 @@ impl Component for EventCalendar {
                                 </div>
                             </div>
                             <footer class="card-footer">
-
+                                {bulk_actions}
                             </footer>
                         </div>
+                        <UpNext {progress} time_zone={tz} onclick={onclick_event.clone()} />
                     </div>
                     <div class="column is-three-fifths calendar-base">
+                        <CalendarSwitcher onchange={link.callback(|_| EventCalendarMsg::Refresh)} />
                         <nav class="level">
                             // Spamming the ID so the onclick works, hacky.
                             <p class="level-left" onclick={&chevron_click} id="cal_month_prev">
@@ -399,13 +1048,45 @@ impl Component for EventCalendar {
                             </p>
                             <p class="level-item has-text-centered">
                                 <div>
-                                    <p class="heading">{&date.format("%Y")}</p>
-                                    <p class="title">{&date.format("%B")}</p>
+                                    <p class="heading">{heading}</p>
+                                    <p class="title">{title}</p>
                                 </div>
                             </p>
+                            <p class="level-item">
+                                <span class="buttons has-addons">
+                                    {view_link(CalendarView::Month)}
+                                    {view_link(CalendarView::Week)}
+                                    {view_link(CalendarView::Day)}
+                                </span>
+                            </p>
+                            <p class="level-item">
+                                <span class="buttons has-addons">
+                                    <button class="button" title={undo_title} disabled={history.undo.is_empty()}
+                                        onclick={link.callback(|_| EventCalendarMsg::Undo)}>{"undo"}</button>
+                                    <button class="button" title={redo_title} disabled={history.redo.is_empty()}
+                                        onclick={link.callback(|_| EventCalendarMsg::Redo)}>{"redo"}</button>
+                                </span>
+                            </p>
+                            <p class="level-item">
+                                <a class="button" title="Share the selected events, or everything upcoming"
+                                    onclick={link.callback(|_| EventCalendarMsg::Share)}>{"share"}</a>
+                            </p>
+                            <p class="level-item">
+                                <span class="select is-small">
+                                    <select title="Only show and export events with this tag" onchange={ontagfilter}>
+                                        <option value="" selected={self.tag_filter.is_none()}>{"all tags"}</option>
+                                        {all_tags.iter().map(|tag| html! {
+                                            <option value={tag.clone()} selected={self.tag_filter.as_ref() == Some(tag)}>{tag}</option>
+                                        }).collect::<Html>()}
+                                    </select>
+                                </span>
+                            </p>
                             <p class="level-item" onclick={&onexport}>
                                 <a class="button" id="cal_export_events">{"export"}</a>
                             </p>
+                            <p class="level-item" onclick={&onexport_ical}>
+                                <a class="button" id="cal_export_ical">{"ical"}</a>
+                            </p>
                             <p class="level-right" onclick={&chevron_click} id="cal_month_next">
                                 <button class="button" id="cal_month_next">
                                     <span class="icon is-small" id="cal_month_next">
@@ -414,22 +1095,33 @@ impl Component for EventCalendar {
                                 </button>
                             </p>
                         </nav>
-                        <table id="bynger_cal" class="table is-fullwidth is-striped">
-                            <thead>
-                                <tr class="">
-                                    <th>{"MON"}</th>
-                                    <th>{"TUE"}</th>
-                                    <th>{"WED"}</th>
-                                    <th>{"THU"}</th>
-                                    <th>{"FRI"}</th>
-                                    <th>{"SAT"}</th>
-                                    <th>{"SUN"}</th>
-                                </tr>
-                            </thead>
-                            <tbody>
-                                {weeks}
-                            </tbody>
-                        </table>
+                        if self.view != CalendarView::Month {
+                            <TimeGrid
+                                days={grid_days}
+                                events={grid_events}
+                                time_zone={tz}
+                                onclick={onclick_event.clone()}
+                                tags={tags.clone()}
+                                colors={grid_colors}
+                            />
+                        } else {
+                            <table id="bynger_cal" class="table is-fullwidth is-striped">
+                                <thead>
+                                    <tr class="">
+                                        <th>{"MON"}</th>
+                                        <th>{"TUE"}</th>
+                                        <th>{"WED"}</th>
+                                        <th>{"THU"}</th>
+                                        <th>{"FRI"}</th>
+                                        <th>{"SAT"}</th>
+                                        <th>{"SUN"}</th>
+                                    </tr>
+                                </thead>
+                                <tbody>
+                                    {weeks}
+                                </tbody>
+                            </table>
+                        }
                     </div>
                 </div>
             </div>
@@ -438,8 +1130,25 @@ impl Component for EventCalendar {
                     scheduled_event={self.active_event.clone().unwrap()}
                     onclosed={onclick_event_close}
                     onwatched={onclick_event_watched}
+                    onunwatched={onclick_event_unwatched}
                     onremove={onclick_event_remove}
                     onreschedule={onclick_event_reschedule}
+                    {onshiftshow}
+                    {onredistributeshow}
+                    onmovecalendar={link.callback(|(event_id, calendar)| EventCalendarMsg::MoveEventToCalendar(event_id, calendar))}
+                    onparty={link.callback(|(event_id, party)| EventCalendarMsg::SetParty(event_id, party))}
+                    ontags={link.callback(|(event_id, event_tags, show_tags)| EventCalendarMsg::SetTags(event_id, event_tags, show_tags))}
+                    ontagcolor={link.callback(|(tag, color)| EventCalendarMsg::SetTagColor(tag, color))}
+                    tags={tags.clone()}
+                    calendars={calendars.clone()}
+                />
+            }
+            if let Some((show_id, remaining)) = &self.redistributing {
+                <ScheduleShow
+                    show_id={show_id.to_string()}
+                    media_type={MediaType::tv}
+                    on_cancel={ctx.link().callback(|_| EventCalendarMsg::RedistributeClosed)}
+                    remaining={Some(remaining.clone())}
                 />
             }
             </>
@@ -1,21 +1,36 @@
 use chrono::{DateTime, Utc};
-use crate::events::ScheduledEvent;
+use crate::calendars::{calendar_select, selected_calendar, Calendars};
+use crate::event_manager::{EventManager, ShowScope};
+use crate::events::{ScheduledEvent, WatchParty, WatchRecord};
+use crate::time_zone::UserTimeZone;
 use crate::search_client::{MediaType, TMDB};
 use crate::site_config::ByngerStore;
 use gloo::storage::{LocalStorage, Storage};
+use std::rc::Rc;
 use uuid::Uuid;
 
 use yew::prelude::*;
 use yew::{html, Callback, Component, Context, Html};
 use crate::datetime_picker::DateTimePicker;
+use crate::tags::{parse_tags, tag_chips, Tags};
+use crate::ui_helpers::UiHelpers;
 
 #[derive(Clone, PartialEq, Properties)]
 pub struct EventDetailsProps {
     pub scheduled_event: ScheduledEvent,
     pub onclosed: Callback<bool>,
     pub onremove: Callback<Uuid>,
-    pub onwatched: Callback<Uuid>,
-    pub onreschedule: Callback<(Uuid, DateTime<Utc>)>
+    pub onwatched: Callback<(Uuid, WatchRecord)>,
+    pub onunwatched: Callback<Uuid>,
+    pub onreschedule: Callback<(Uuid, DateTime<Utc>)>,
+    pub onshiftshow: Callback<(usize, ShowScope, i64)>, // Show id, which events, days.
+    pub onredistributeshow: Callback<(usize, ShowScope)>,
+    pub onmovecalendar: Callback<(Uuid, Uuid)>, // Event id, calendar id.
+    pub onparty: Callback<(Uuid, Option<WatchParty>)>,
+    pub ontags: Callback<(Uuid, Vec<String>, Vec<String>)>, // Event id, event tags, show tags.
+    pub ontagcolor: Callback<(String, String)>, // Tag, color.
+    pub tags: Rc<Tags>,
+    pub calendars: Rc<Calendars>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -139,14 +154,113 @@ impl Component for EventDetails {
         let ors = ctx.props().onreschedule.clone();
         let onclose = Callback::from(move |_| oce.emit(true));
         let onremove = Callback::from(move |_| ore.emit(event.uuid));
-        let onwatched = Callback::from(move |_| owe.emit(event.uuid));
+        let onwatched = Callback::from(move |_| {
+            let rating = UiHelpers::get_value_from_input_by_id("#watchRating")
+                .and_then(|raw| raw.parse::<u8>().ok());
+            let notes = UiHelpers::get_value_from_input_by_id("#watchNotes")
+                .map(|raw| raw.trim().to_string())
+                .filter(|notes| !notes.is_empty());
+            owe.emit((event.uuid, WatchRecord { rating, notes, ..WatchRecord::now() }))
+        });
+        let oue = ctx.props().onunwatched.clone();
+        let onunwatched = Callback::from(move |_| oue.emit(event.uuid));
         let onreschedule = Callback::from(move |dt:DateTime<Utc>| ors.emit((event.uuid, dt.clone())));
+        let omc = ctx.props().onmovecalendar.clone();
+        let calendars = ctx.props().calendars.clone();
+        let onmovecalendar = Callback::from(move |_: Event| omc.emit((event.uuid, selected_calendar(&calendars, "#eventCalendar"))));
+
+        let opa = ctx.props().onparty.clone();
+        let onparty = Callback::from(move |_| {
+            let participants = UiHelpers::get_value_from_input_by_id("#partyParticipants").unwrap_or_default();
+            let location = UiHelpers::get_value_from_input_by_id("#partyLocation").unwrap_or_default();
+            opa.emit((event.uuid, WatchParty::from_inputs(&participants, &location)))
+        });
+        let opa = ctx.props().onparty.clone();
+        let oncancelparty = Callback::from(move |_| opa.emit((event.uuid, None)));
+        let party = event.party.clone().unwrap_or_default();
+
+        let tags = &ctx.props().tags;
+        let show_tags = tags.show_tags(&event);
+        let event_tags = tags.event_tags(&event);
+        let ota = ctx.props().ontags.clone();
+        let ontags = Callback::from(move |_| {
+            let tags_from_input = |id: &str| parse_tags(&UiHelpers::get_value_from_input_by_id(id).unwrap_or_default());
+            ota.emit((event.uuid, tags_from_input("#eventTags"), tags_from_input("#showTags")))
+        });
+        let tag_colors = event_tags
+            .iter()
+            .map(|tag| {
+                let otc = ctx.props().ontagcolor.clone();
+                let name = tag.clone();
+                let onchange = Callback::from(move |e: Event| {
+                    if let Some(color) = UiHelpers::get_value_from_event(&e) {
+                        otc.emit((name.clone(), color))
+                    }
+                });
+                html! {
+                    <label class="tag-color mr-2">
+                        <input type="color" value={tags.color(tag)} {onchange} />
+                        {tag}
+                    </label>
+                }
+            })
+            .collect::<Html>();
+
+        // Whole show rescheduling, only offered for episodes.
+        let show_id = event.episode.as_ref().map(|ep| ep.show_id);
+        let scheduled_date = event.scheduled_date;
+        let show_scope = move || {
+            match UiHelpers::get_value_from_input_by_id("#showScope").as_deref() {
+                Some("from_here") => ShowScope::From(scheduled_date),
+                _ => ShowScope::Unwatched,
+            }
+        };
+        let oss = ctx.props().onshiftshow.clone();
+        let onshiftshow = Callback::from(move |_| {
+            let days = UiHelpers::get_value_from_input_by_id("#showShiftDays")
+                .and_then(|raw| raw.parse::<i64>().ok())
+                .unwrap_or(0);
+            if let (Some(show_id), true) = (show_id, days != 0) {
+                oss.emit((show_id, show_scope(), days));
+            }
+        });
+        let ors = ctx.props().onredistributeshow.clone();
+        let onredistributeshow = Callback::from(move |_| {
+            if let Some(show_id) = show_id {
+                ors.emit((show_id, show_scope()));
+            }
+        });
 
 
         let mut watched_class = classes!("button", "is-info");
-        if event.watched {
+        if event.watched() {
             watched_class.push(classes!("event-watched", "is-outlined"));
         }
+        let watched_label = if event.watched() { "WATCHED AGAIN" } else { "WATCHED" };
+
+        // This event's watches, plus any from other times the same episode was scheduled.
+        let tz = UserTimeZone::get();
+        let episode_watches = event
+            .episode
+            .as_ref()
+            .map_or(0, |ep| EventManager::create().episode_watches(ep.id).len());
+        let watch_log = event
+            .watch_log
+            .iter()
+            .rev()
+            .map(|wr| {
+                let rating = wr.rating.map(|r| "★".repeat(r as usize)).unwrap_or_default();
+                html! {
+                    <li>
+                        {wr.watched_at.with_timezone(&tz).format("%a %b %d %Y %R").to_string()}
+                        <span class="has-text-warning ml-2">{rating}</span>
+                        if let Some(notes) = &wr.notes {
+                            <span class="ml-2 is-italic">{notes}</span>
+                        }
+                    </li>
+                }
+            })
+            .collect::<Html>();
 
         html! {
             if let Some(det) = &self.details {
@@ -177,12 +291,40 @@ impl Component for EventDetails {
                                         <DateTimePicker label="RESCHEDULE" onclick={onreschedule}/>
                                     </p>
                                 </div>
+                                <div class="field is-grouped is-grouped-centered">
+                                    <p class="control" onchange={onmovecalendar}>
+                                        {calendar_select(&ctx.props().calendars, "eventCalendar", event.calendar)}
+                                    </p>
+                                </div>
+                                <div class="field is-grouped is-grouped-centered">
+                                    <p class="control">
+                                        <span class="select">
+                                            <select id="watchRating">
+                                                <option value="" selected=true>{"No Rating"}</option>
+                                                {(1..=5u8).map(|r| {
+                                                    let stars = "★".repeat(r as usize);
+                                                    html!{ <option value={r.to_string()}>{stars}</option> }
+                                                }).collect::<Html>()}
+                                            </select>
+                                        </span>
+                                    </p>
+                                    <p class="control is-expanded">
+                                        <input class="input" id="watchNotes" type="text" placeholder="Notes" />
+                                    </p>
+                                </div>
                                 <div class="field is-grouped is-grouped-centered">
                                     <p class="control">
                                         <button class={watched_class} aria-label="watched" onclick={onwatched}>
-                                            {"WATCHED"}
+                                            {watched_label}
                                         </button>
                                     </p>
+                                    if event.watched() {
+                                        <p class="control">
+                                            <button class="button is-info is-light" aria-label="unwatch" onclick={onunwatched}>
+                                                {"UNDO WATCH"}
+                                            </button>
+                                        </p>
+                                    }
                                     <p class="control">
                                         <button class="button is-danger" aria-label="remove" onclick={onremove}>
                                             {"REMOVE"}
@@ -190,6 +332,85 @@ impl Component for EventDetails {
                                     </p>
                                 </div>
                             </div>
+                            if event.watched() || episode_watches > 0 {
+                                <div class="box watch-log">
+                                    <h1 class="is-size-6 mb-2">{"Watch Log"}</h1>
+                                    <ul>{watch_log}</ul>
+                                    if episode_watches > event.watch_log.len() {
+                                        <p class="is-size-7 mt-2">
+                                            {format!("Watched {episode_watches} times in total across every time it was scheduled.")}
+                                        </p>
+                                    }
+                                </div>
+                            }
+                            <div class="box event-tags-box">
+                                <h1 class="is-size-6 mb-2">{"Tags"} <span class="ml-2">{tag_chips(tags, &event_tags)}</span></h1>
+                                <div class="field">
+                                    <input class="input is-small" id="eventTags" type="text"
+                                        placeholder="This event, e.g. comfort, kids" value={event.tags.join(", ")} />
+                                </div>
+                                <div class="field">
+                                    <input class="input is-small" id="showTags" type="text"
+                                        placeholder="Every event of this show, e.g. horror" value={show_tags.join(", ")} />
+                                </div>
+                                <div class="buttons are-small is-centered">
+                                    <button class="button is-primary" onclick={ontags}>{"SAVE TAGS"}</button>
+                                </div>
+                                if !event_tags.is_empty() {
+                                    <p class="is-size-7">{tag_colors}</p>
+                                }
+                            </div>
+                            <div class="box watch-party">
+                                <h1 class="is-size-6 mb-2">{"Watch Party"}</h1>
+                                <div class="field">
+                                    <input class="input is-small" id="partyParticipants" type="text"
+                                        placeholder="Who's coming, e.g. Sam, Alex <alex@example.com>"
+                                        value={party.participants.join(", ")} />
+                                </div>
+                                <div class="field">
+                                    <input class="input is-small" id="partyLocation" type="text"
+                                        placeholder="Where, or a streaming link"
+                                        value={party.location.clone().unwrap_or_default()} />
+                                </div>
+                                <div class="buttons are-small is-centered">
+                                    <button class="button is-warning" onclick={onparty}>
+                                        {if event.party.is_some() { "UPDATE PARTY" } else { "MAKE IT A PARTY" }}
+                                    </button>
+                                    if event.party.is_some() {
+                                        <button class="button is-warning is-outlined" onclick={oncancelparty}>
+                                            {"CANCEL PARTY"}
+                                        </button>
+                                    }
+                                </div>
+                            </div>
+                            if show_id.is_some() {
+                                <div class="box whole-show">
+                                    <h1 class="is-size-6 mb-2">{"Whole Show"}</h1>
+                                    <div class="field is-grouped is-grouped-centered is-grouped-multiline">
+                                        <p class="control">
+                                            <span class="select is-small">
+                                                <select id="showScope">
+                                                    <option value="unwatched" selected=true>{"All Unwatched"}</option>
+                                                    <option value="from_here">{"From This Episode On"}</option>
+                                                </select>
+                                            </span>
+                                        </p>
+                                        <p class="control">
+                                            <input class="input is-small shift-days" id="showShiftDays" type="number" value="1" />
+                                        </p>
+                                        <p class="control">
+                                            <button class="button is-small is-warning" onclick={onshiftshow}>
+                                                {"SHIFT DAYS"}
+                                            </button>
+                                        </p>
+                                        <p class="control">
+                                            <button class="button is-small is-info is-outlined" onclick={onredistributeshow}>
+                                                {"REDISTRIBUTE"}
+                                            </button>
+                                        </p>
+                                    </div>
+                                </div>
+                            }
                          </section>
                     </div>
                 </div>
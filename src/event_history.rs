@@ -0,0 +1,109 @@
+use std::collections::HashMap;
+
+use gloo::storage::errors::StorageError;
+use gloo::storage::{LocalStorage, Storage};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::events::ScheduledEvent;
+use crate::site_config::ByngerStore;
+
+// How many changes we keep around to step back through.
+const HISTORY_LIMIT: usize = 50;
+
+// A single write to the schedule, kept as the events it touched before and after.
+// Undoing swaps `after` back out for `before`, redoing does the opposite.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EventCommand {
+    pub label: String,
+    pub before: Vec<ScheduledEvent>, // Old versions of changed events, plus removed ones.
+    pub after: Vec<ScheduledEvent>,  // New versions of changed events, plus added ones.
+}
+
+impl EventCommand {
+    // Works out what changed between two versions of the schedule, None if nothing did.
+    pub fn diff(label: &str, old: &[ScheduledEvent], new: &[ScheduledEvent]) -> Option<Self> {
+        let old_by_id: HashMap<Uuid, &ScheduledEvent> = old.iter().map(|se| (se.uuid, se)).collect();
+        let new_by_id: HashMap<Uuid, &ScheduledEvent> = new.iter().map(|se| (se.uuid, se)).collect();
+
+        let before = old
+            .iter()
+            .filter(|se| new_by_id.get(&se.uuid) != Some(se))
+            .cloned()
+            .collect::<Vec<ScheduledEvent>>();
+        let after = new
+            .iter()
+            .filter(|se| old_by_id.get(&se.uuid) != Some(se))
+            .cloned()
+            .collect::<Vec<ScheduledEvent>>();
+
+        if before.is_empty() && after.is_empty() {
+            return None;
+        }
+
+        Some(EventCommand {
+            label: label.to_string(),
+            before,
+            after,
+        })
+    }
+
+    // Rolls the given events from one side of the command to the other.
+    fn swap(events: &mut Vec<ScheduledEvent>, from: &[ScheduledEvent], to: &[ScheduledEvent]) {
+        events.retain(|se| !from.iter().any(|f| f.uuid == se.uuid));
+        events.extend(to.iter().cloned());
+        events.sort_unstable_by_key(|se| se.scheduled_date);
+    }
+
+    pub fn undo(&self, events: &mut Vec<ScheduledEvent>) {
+        Self::swap(events, &self.after, &self.before);
+    }
+
+    pub fn redo(&self, events: &mut Vec<ScheduledEvent>) {
+        Self::swap(events, &self.before, &self.after);
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EventHistory {
+    pub undo: Vec<EventCommand>,
+    pub redo: Vec<EventCommand>,
+}
+
+impl EventHistory {
+    pub fn load() -> Self {
+        let mut history: Self = LocalStorage::get(ByngerStore::EventHistory.to_string()).unwrap_or_default();
+        // Undoing back to an event saved before watch logs shouldn't lose it being watched.
+        history
+            .undo
+            .iter_mut()
+            .chain(history.redo.iter_mut())
+            .flat_map(|c| c.before.iter_mut().chain(c.after.iter_mut()))
+            .for_each(ScheduledEvent::upgrade_watched);
+
+        history
+    }
+
+    pub fn store(&self) -> Result<(), StorageError> {
+        LocalStorage::set(ByngerStore::EventHistory.to_string(), self)
+    }
+
+    // A fresh change makes anything we'd undone unreachable.
+    pub fn record(&mut self, command: EventCommand) -> Result<(), StorageError> {
+        self.undo.push(command);
+        if self.undo.len() > HISTORY_LIMIT {
+            self.undo.remove(0);
+        }
+        self.redo.clear();
+
+        self.store()
+    }
+
+    pub fn undo_label(&self) -> Option<&str> {
+        self.undo.last().map(|c| c.label.as_str())
+    }
+
+    pub fn redo_label(&self) -> Option<&str> {
+        self.redo.last().map(|c| c.label.as_str())
+    }
+}
@@ -1,16 +1,26 @@
 use std::error::Error;
 
 use chrono::{DateTime, Duration, Utc};
+use chrono_tz::Tz;
 use gloo::storage::errors::StorageError;
 use gloo::storage::{LocalStorage, Storage};
 use std::ops::Add;
 use uuid::Uuid;
+use weblog::console_error;
 
 
-use crate::events::ScheduledEvent;
+use crate::catch_up::{plan_catch_up, CatchUpStrategy};
+use crate::event_history::{EventCommand, EventHistory};
+use crate::events::{ScheduledEvent, WatchParty, WatchRecord};
 use crate::search_client::MediaType;
+use crate::sync::EditTimes;
+use crate::tags::Tags;
+use crate::time_zone::UserTimeZone;
 use crate::ByngerStore;
 
+// Bumped whenever stored events need converting, see EventManager::migrate.
+const STORAGE_VERSION: u32 = 1;
+
 pub enum CsvType {
     GCAL, // Google Cal
     // --- GCAL Format ---
@@ -26,17 +36,52 @@ pub enum CsvType {
     ICAL, // Not really a CSV format, actually.
 }
 
+// Which of a show's events a whole-show reschedule touches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShowScope {
+    Unwatched,
+    From(DateTime<Utc>), // Unwatched and scheduled at or after this point.
+}
+
 pub struct EventManager {
     storage: String,
     pub events: Vec<ScheduledEvent>,
+    stored: Vec<ScheduledEvent>, // What's in storage, so each write can be recorded for undo.
 }
 
 impl EventManager {
     pub(crate) fn create() -> Self {
         let storage = format!("{}", ByngerStore::ScheduledEvents);
-        let events = LocalStorage::get(&*storage).unwrap_or_default();
+        let mut events: Vec<ScheduledEvent> = LocalStorage::get(&*storage).unwrap_or_default();
+        events.iter_mut().for_each(ScheduledEvent::upgrade_watched);
+        let stored = events.clone();
+
+        let mut em = EventManager { storage, events, stored };
+        if let Err(e) = em.migrate() {
+            console_error!(format!("BYNGER - Event Migration Failed - {}", e));
+        }
+
+        em
+    }
 
-        EventManager { storage, events }
+    // Brings events saved by older versions up to date, once.
+    // Version 1: events used to be saved as local wall clock times labelled UTC.
+    fn migrate(&mut self) -> Result<(), StorageError> {
+        let version_key = ByngerStore::StorageVersion.to_string();
+        let version: u32 = LocalStorage::get(&version_key).unwrap_or(0);
+        if version >= STORAGE_VERSION {
+            return Ok(());
+        }
+        if version < 1 && !self.events.is_empty() {
+            let tz = UserTimeZone::get();
+            for se in self.events.iter_mut() {
+                se.scheduled_date = UserTimeZone::to_utc(&tz, se.scheduled_date.naive_utc());
+            }
+            // Not a change anyone made, so there's nothing to undo.
+            self.write()?;
+        }
+
+        LocalStorage::set(version_key, STORAGE_VERSION)
     }
 
     // fn add_event(&mut self, scheduled_event: ScheduledEvent) {
@@ -44,22 +89,94 @@ impl EventManager {
     //     self.events.push(scheduled_event)
     // }
 
-    fn store(&self) -> Result<(), StorageError> {
-        LocalStorage::set(&self.storage, self.events.to_vec())
+    // Every change goes through here, so every change can be undone.
+    // The change is only recorded once it's saved, a failed write has nothing to undo.
+    fn store(&mut self, label: &str) -> Result<(), StorageError> {
+        let command = EventCommand::diff(label, &self.stored, &self.events);
+        self.write()?;
+        if let Some(command) = command {
+            let mut history = EventHistory::load();
+            history.record(command)?;
+        }
+
+        Ok(())
+    }
+
+    fn write(&mut self) -> Result<(), StorageError> {
+        LocalStorage::set(&self.storage, self.events.to_vec())?;
+        // Sync goes by when each change was made here, not when it happened to be sent.
+        let mut edits = EditTimes::load();
+        edits.record(&self.stored, &self.events, Utc::now().timestamp_millis());
+        edits.store()?;
+        self.stored = self.events.clone();
+
+        Ok(())
+    }
+
+    // Steps back one change, handing back what was undone.
+    pub fn undo(&mut self) -> Result<Option<String>, StorageError> {
+        let mut history = EventHistory::load();
+        let Some(command) = history.undo.pop() else {
+            return Ok(None);
+        };
+        command.undo(&mut self.events);
+        self.write()?;
+        let label = command.label.clone();
+        history.redo.push(command);
+        history.store()?;
+
+        Ok(Some(label))
+    }
+
+    pub fn redo(&mut self) -> Result<Option<String>, StorageError> {
+        let mut history = EventHistory::load();
+        let Some(command) = history.redo.pop() else {
+            return Ok(None);
+        };
+        command.redo(&mut self.events);
+        self.write()?;
+        let label = command.label.clone();
+        history.undo.push(command);
+        history.store()?;
+
+        Ok(Some(label))
     }
 
     fn purge_events(&mut self) -> Result<(), StorageError> {
         self.events.clear();
 
-        self.store()
+        self.store("Clear schedule")
     }
 
-    pub fn watched_event(&mut self, event_id: Uuid) -> Result<(), StorageError> {
+    // Adds a watch to the event's log, a second one is a rewatch.
+    pub fn log_watch(&mut self, event_id: Uuid, record: WatchRecord) -> Result<(), StorageError> {
         if let Some(pos) = self.events.iter().position(|se| se.uuid == event_id) {
-            self.events[pos].watched = !self.events[pos].watched;
+            self.events[pos].watch_log.push(record);
         }
 
-        self.store()
+        self.store("Log watch")
+    }
+
+    // Drops the most recent watch, the event goes back to unwatched once the log is empty.
+    pub fn unlog_watch(&mut self, event_id: Uuid) -> Result<(), StorageError> {
+        if let Some(pos) = self.events.iter().position(|se| se.uuid == event_id) {
+            self.events[pos].watch_log.pop();
+        }
+
+        self.store("Remove watch")
+    }
+
+    // Every watch of an episode across all the events it was scheduled in, oldest first.
+    pub fn episode_watches(&self, episode_id: usize) -> Vec<WatchRecord> {
+        let mut watches = self
+            .events
+            .iter()
+            .filter(|se| se.episode.as_ref().is_some_and(|ep| ep.id == episode_id))
+            .flat_map(|se| se.watch_log.iter().cloned())
+            .collect::<Vec<WatchRecord>>();
+        watches.sort_by_key(|w| w.watched_at);
+
+        watches
     }
 
     pub fn reschedule_event(&mut self, event_id: Uuid, datetime: DateTime<Utc>) -> Result<(), StorageError> {
@@ -67,7 +184,7 @@ impl EventManager {
             self.events[pos].scheduled_date = datetime;
         }
 
-        self.store()
+        self.store("Reschedule event")
     }
 
     pub fn remove_event(&mut self, event_id: Uuid) -> Result<(), StorageError> {
@@ -75,33 +192,175 @@ impl EventManager {
             self.events.remove(pos);
         }
 
-        self.store()
+        self.store("Remove event")
     }
 
-    pub fn add_events(&mut self, mut events: Vec<ScheduledEvent>) -> Result<(), StorageError> {
+    pub fn show_events(&self, show_id: usize, scope: ShowScope) -> Vec<ScheduledEvent> {
+        self.events
+            .iter()
+            .filter(|se| !se.watched())
+            .filter(|se| se.episode.as_ref().is_some_and(|ep| ep.show_id == show_id))
+            .filter(|se| match scope {
+                ShowScope::Unwatched => true,
+                ShowScope::From(from) => se.scheduled_date >= from,
+            })
+            .cloned()
+            .collect()
+    }
+
+    // Moves a show's remaining events by the given number of days, all in one write.
+    pub fn shift_show_events(&mut self, show_id: usize, scope: ShowScope, days: i64) -> Result<(), StorageError> {
+        let shifting = self
+            .show_events(show_id, scope)
+            .into_iter()
+            .map(|se| se.uuid)
+            .collect::<Vec<Uuid>>();
+
+        self.shift_events(&shifting, days)
+    }
+
+    // The batch methods below each touch any number of events but only write storage once.
+    pub fn shift_events(&mut self, event_ids: &[Uuid], days: i64) -> Result<(), StorageError> {
+        for se in self.events.iter_mut().filter(|se| event_ids.contains(&se.uuid)) {
+            se.scheduled_date = se.scheduled_date.add(Duration::days(days));
+        }
+        self.events.sort_unstable_by_key(|se| se.scheduled_date);
+
+        self.store("Shift events")
+    }
+
+    pub fn set_watched(&mut self, event_ids: &[Uuid], watched: bool) -> Result<(), StorageError> {
+        for se in self.events.iter_mut().filter(|se| event_ids.contains(&se.uuid)) {
+            match watched {
+                true if !se.watched() => se.watch_log.push(WatchRecord::now()),
+                true => {}
+                // Like unlog_watch, only the latest watch goes so ratings and rewatches aren't lost.
+                false => {
+                    se.watch_log.pop();
+                }
+            }
+        }
+
+        self.store(if watched { "Mark watched" } else { "Mark unwatched" })
+    }
+
+    pub fn remove_events(&mut self, event_ids: &[Uuid]) -> Result<(), StorageError> {
+        self.events.retain(|se| !event_ids.contains(&se.uuid));
+
+        self.store("Remove events")
+    }
+
+    // Everything scheduled for these shows, watched or not.
+    pub fn remove_show_events(&mut self, show_ids: &[usize]) -> Result<(), StorageError> {
+        self.events
+            .retain(|se| se.episode.as_ref().is_none_or(|ep| !show_ids.contains(&ep.show_id)));
+
+        self.store("Remove shows")
+    }
+
+    // Rolls missed (past and unwatched) events forward, returning how many events moved.
+    pub fn catch_up(&mut self, strategy: CatchUpStrategy, tz: &Tz, now: DateTime<Utc>) -> Result<usize, StorageError> {
+        let moved = plan_catch_up(&self.events, strategy, tz, now);
+        if moved.is_empty() {
+            return Ok(0);
+        }
+        for se in self.events.iter_mut() {
+            if let Some(updated) = moved.iter().find(|m| m.uuid == se.uuid) {
+                se.scheduled_date = updated.scheduled_date;
+            }
+        }
+        self.events.sort_unstable_by_key(|se| se.scheduled_date);
+
+        self.store("Catch up").map(|_| moved.len())
+    }
+
+    // Swaps out a batch of events for their replacements, all in one write.
+    pub fn replace_events(&mut self, replaced: &[Uuid], mut events: Vec<ScheduledEvent>) -> Result<(), StorageError> {
+        self.events.retain(|se| !replaced.contains(&se.uuid));
         self.events.append(&mut events);
+        self.events.sort_unstable_by_key(|se| se.scheduled_date);
+
+        self.store("Redistribute show")
+    }
+
+    // None turns a watch party back in to a regular event.
+    pub fn set_party(&mut self, event_id: Uuid, party: Option<WatchParty>) -> Result<(), StorageError> {
+        if let Some(pos) = self.events.iter().position(|se| se.uuid == event_id) {
+            self.events[pos].party = party;
+        }
+
+        self.store("Watch party")
+    }
+
+    pub fn set_tags(&mut self, event_id: Uuid, tags: Vec<String>) -> Result<(), StorageError> {
+        if let Some(pos) = self.events.iter().position(|se| se.uuid == event_id) {
+            self.events[pos].tags = tags;
+        }
+
+        self.store("Tag event")
+    }
+
+    pub fn move_to_calendar(&mut self, event_ids: &[Uuid], calendar: Uuid) -> Result<(), StorageError> {
         self.events
-            .sort_unstable_by(|a, b| a.scheduled_date.cmp(&b.scheduled_date));
-        self.store() // commit new schedule to LocalStorage
+            .iter_mut()
+            .filter(|se| event_ids.contains(&se.uuid))
+            .for_each(|se| se.calendar = calendar);
+
+        self.store("Move to calendar")
+    }
+
+    // Swaps in a whole new set of events, e.g. what came back from a sync.
+    pub fn set_events(&mut self, mut events: Vec<ScheduledEvent>, label: &str) -> Result<(), StorageError> {
+        events.sort_unstable_by_key(|se| se.scheduled_date);
+        self.events = events;
+
+        self.store(label)
     }
 
-    pub fn events_as_csv(&mut self, csv_type: CsvType) -> Result<String, Box<dyn Error>> {
+    pub fn add_events(&mut self, mut events: Vec<ScheduledEvent>) -> Result<(), StorageError> {
+        self.events.append(&mut events);
+        self.events.sort_unstable_by_key(|se| se.scheduled_date);
+        self.store("Schedule events") // commit new schedule to LocalStorage
+    }
+
+    // Only events carrying the tag when one is given, show tags included.
+    pub fn events_as_csv(&mut self, csv_type: CsvType, tag: Option<&str>) -> Result<String, Box<dyn Error>> {
         // A naive CSV export implementation.
         let mut csv_string = String::new();
+        let tags = Tags::load();
+        let tagged = |se: &&ScheduledEvent| tag.is_none_or(|tag| tags.has_tag(se, tag));
         match csv_type {
             CsvType::GCAL => {
                 let date_fmt = "%D"; // Month-day-year format. Same as %m/%d/%y
+                let time_zone = UserTimeZone::get();
                 let time_fmt = "%I:%M %p";
+                // Every field goes out wrapped in quotes, so any inside them get doubled.
+                let quoted = |text: &str| text.replace('"', "\"\"");
                 let tv_subject = |event: &ScheduledEvent| {
                     let ep = event.episode.clone().expect("Missing Episode");
-                    format!(
+                    quoted(&format!(
                         "{} | s{}e{}",
                         &ep.show_name, &ep.season_number, &ep.episode_number
-                    )
+                    ))
                 };
                 let mv_subject = |event: &ScheduledEvent| {
                     let mv = event.movie.clone().expect("Missing Movie");
-                    format!("{} | Runtime: {}", mv.show_name, mv.runtime)
+                    quoted(&format!("{} | Runtime: {}", mv.show_name, mv.runtime))
+                };
+                // GCAL has no attendee column, so watch party guests go in the description.
+                let description = |event: &ScheduledEvent, text: &str| match &event.party {
+                    Some(party) if !party.participants.is_empty() => {
+                        quoted(&format!("{text} | Watch party with {}", party.participants.join(", ")))
+                    }
+                    _ => quoted(text),
+                };
+                let location = |event: &ScheduledEvent| {
+                    event
+                        .party
+                        .as_ref()
+                        .and_then(|party| party.location.as_deref())
+                        .map(quoted)
+                        .unwrap_or_default()
                 };
 
                 // Could we just use serde?
@@ -110,45 +369,42 @@ impl EventManager {
                         "Subject, Start Date, Start Time, End Date, End Time, All Day Event, Description, Location, Private \n",
                     );
 
-                self.events
-                    .sort_unstable_by(|a, b| a.scheduled_date.cmp(&b.scheduled_date));
-                for event in &self.events {
+                self.events.sort_unstable_by_key(|se| se.scheduled_date);
+                for event in self.events.iter().filter(tagged) {
+                    // GCAL imports these as wall clock times, so they go out in the user's zone.
+                    let start = event.scheduled_date.with_timezone(&time_zone);
                     match event.media_type {
                         MediaType::tv => {
                             let ep = &event.episode.clone().unwrap();
+                            let end = start.add(Duration::minutes(ep.episode_run_time as i64));
 
                             csv_string.push_str(&format!(
                                 "\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\"\n",
                                 tv_subject(event),
-                                event.scheduled_date.format(date_fmt),
-                                event.scheduled_date.format(time_fmt),
-                                &event.scheduled_date.format(date_fmt), // Eps end same day.
-                                event
-                                    .scheduled_date
-                                    .add(Duration::minutes(ep.episode_run_time as i64))
-                                    .format(time_fmt),
+                                start.format(date_fmt),
+                                start.format(time_fmt),
+                                end.format(date_fmt), // May run past midnight.
+                                end.format(time_fmt),
                                 "FALSE",
-                                ep.name,
-                                "",
+                                description(event, &ep.name),
+                                location(event),
                                 "TRUE"
                             ));
                         }
                         MediaType::movie => {
                             let mv = &event.movie.clone().unwrap();
+                            let end = start.add(Duration::minutes(mv.runtime as i64));
 
                             csv_string.push_str(&format!(
                                 "\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\"\n",
                                 mv_subject(event),
-                                event.scheduled_date.format(date_fmt),
-                                event.scheduled_date.format(time_fmt),
-                                &event.scheduled_date.format(date_fmt), // Eps end same day.
-                                event
-                                    .scheduled_date
-                                    .add(Duration::minutes(mv.runtime as i64))
-                                    .format(time_fmt),
+                                start.format(date_fmt),
+                                start.format(time_fmt),
+                                end.format(date_fmt), // May run past midnight.
+                                end.format(time_fmt),
                                 "FALSE",
-                                mv.show_name,
-                                "",
+                                description(event, &mv.show_name),
+                                location(event),
                                 "TRUE"
                             ));
                         }
@@ -157,7 +413,77 @@ impl EventManager {
                 }
             }
             CsvType::ICAL => {
-                todo!();
+                // RFC 5545 wants UTC stamps in this basic format and CRLF line endings.
+                let stamp_fmt = "%Y%m%dT%H%M%SZ";
+                let now = Utc::now().format(stamp_fmt);
+                let escape = |text: &str| {
+                    text.replace('\\', "\\\\")
+                        .replace(';', "\\;")
+                        .replace(',', "\\,")
+                        .replace('\n', "\\n")
+                };
+
+                csv_string.push_str("BEGIN:VCALENDAR\r\nVERSION:2.0\r\nPRODID:-//Bynger//Bynger//EN\r\n");
+
+                self.events.sort_unstable_by_key(|se| se.scheduled_date);
+                for event in self.events.iter().filter(tagged) {
+                    let (summary, description, runtime) = match event.media_type {
+                        MediaType::tv => {
+                            let ep = event.episode.clone().expect("Missing Episode");
+                            (
+                                format!(
+                                    "{} | s{}e{}",
+                                    &ep.show_name, &ep.season_number, &ep.episode_number
+                                ),
+                                ep.name,
+                                ep.episode_run_time,
+                            )
+                        }
+                        MediaType::movie => {
+                            let mv = event.movie.clone().expect("Missing Movie");
+                            (
+                                format!("{} | Runtime: {}", mv.show_name, mv.runtime),
+                                mv.show_name,
+                                mv.runtime,
+                            )
+                        }
+                        _ => continue,
+                    };
+
+                    // Guests without an email still show up by name, "invalid:nomail" is how GCAL does it.
+                    let mut party = String::new();
+                    if let Some(wp) = &event.party {
+                        if let Some(location) = &wp.location {
+                            party.push_str(&format!("LOCATION:{}\r\n", escape(location)));
+                        }
+                        for participant in &wp.participants {
+                            let (name, email) = WatchParty::attendee(participant);
+                            let address = email.map_or(String::from("invalid:nomail"), |e| format!("mailto:{e}"));
+                            party.push_str(&format!(
+                                "ATTENDEE;CN=\"{}\";ROLE=REQ-PARTICIPANT;PARTSTAT=NEEDS-ACTION:{}\r\n",
+                                name.replace('"', "'"),
+                                address
+                            ));
+                        }
+                    }
+
+                    // Events end when their content does, any padding after them stays free time.
+                    csv_string.push_str(&format!(
+                        "BEGIN:VEVENT\r\nUID:{}@bynger\r\nDTSTAMP:{}\r\nDTSTART:{}\r\nDTEND:{}\r\nSUMMARY:{}\r\nDESCRIPTION:{}\r\n{}END:VEVENT\r\n",
+                        event.uuid,
+                        now,
+                        event.scheduled_date.format(stamp_fmt),
+                        event
+                            .scheduled_date
+                            .add(Duration::minutes(runtime as i64))
+                            .format(stamp_fmt),
+                        escape(&summary),
+                        escape(&description),
+                        party,
+                    ));
+                }
+
+                csv_string.push_str("END:VCALENDAR\r\n");
             }
         };
 
@@ -1,4 +1,4 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 
 //use serde::ser::{Serialize, SerializeStruct, Serializer};
 use crate::schedule_show::{Episode, Movie};
@@ -16,8 +16,121 @@ pub struct ScheduledEvent {
     // Scheduled Events must implement the CalendarSchedulableEvent trait use by the Event Calendar.
     pub episode: Option<Episode>,
     pub movie: Option<Movie>,
+    // Which of the user's calendars this is in, nil being the default one.
     #[serde(default)]
-    pub watched: bool,
+    pub calendar: Uuid,
+    // Some when this is a shared viewing night.
+    #[serde(default)]
+    pub party: Option<WatchParty>,
+    // Just this event's tags, tags on the whole show are kept in Tags.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    // Every time this event was watched, oldest first. Empty means unwatched.
+    #[serde(default)]
+    pub watch_log: Vec<WatchRecord>,
+    // Older saves only had a flag, EventManager turns it in to a log entry on load.
+    #[serde(default, rename = "watched", skip_serializing)]
+    pub legacy_watched: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WatchRecord {
+    pub watched_at: DateTime<Utc>,
+    #[serde(default)]
+    pub rating: Option<u8>, // 1 to 5
+    #[serde(default)]
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct WatchParty {
+    pub participants: Vec<String>, // "Name", "name@example.com" or "Name <name@example.com>"
+    #[serde(default)]
+    pub location: Option<String>, // A place, or a streaming/call link.
+}
+
+impl WatchParty {
+    // From the comma separated participants and location fields, None if both are empty.
+    pub fn from_inputs(participants: &str, location: &str) -> Option<Self> {
+        let participants = participants
+            .split(',')
+            .map(|p| p.trim().to_string())
+            .filter(|p| !p.is_empty())
+            .collect::<Vec<String>>();
+        let location = Some(location.trim().to_string()).filter(|l| !l.is_empty());
+
+        (!participants.is_empty() || location.is_some()).then_some(WatchParty { participants, location })
+    }
+
+    // Splits a participant in to a display name and an email, if they gave one.
+    pub fn attendee(participant: &str) -> (String, Option<String>) {
+        match participant.split_once('<') {
+            Some((name, email)) => {
+                let email = email.trim_end_matches('>').trim().to_string();
+                let name = Some(name.trim()).filter(|n| !n.is_empty()).unwrap_or(&email).to_string();
+                (name, Some(email))
+            }
+            None if participant.contains('@') => (participant.to_string(), Some(participant.to_string())),
+            None => (participant.to_string(), None),
+        }
+    }
+
+    // Up to two letters for a badge, e.g. "Ada Lovelace" is "AL".
+    pub fn initials(participant: &str) -> String {
+        let (name, _) = Self::attendee(participant);
+        name.split_whitespace()
+            .filter_map(|word| word.chars().next())
+            .take(2)
+            .collect::<String>()
+            .to_uppercase()
+    }
+}
+
+impl WatchRecord {
+    pub fn now() -> Self {
+        WatchRecord {
+            watched_at: Utc::now(),
+            rating: None,
+            notes: None,
+        }
+    }
+}
+
+impl ScheduledEvent {
+    pub fn watched(&self) -> bool {
+        !self.watch_log.is_empty()
+    }
+
+    // In minutes, 0 if we don't know.
+    pub fn runtime(&self) -> i64 {
+        let minutes = match self.media_type {
+            MediaType::tv => self.episode.as_ref().map_or(0, |ep| ep.episode_run_time),
+            MediaType::movie => self.movie.as_ref().map_or(0, |mv| mv.runtime),
+            _ => 0,
+        };
+
+        minutes as i64
+    }
+
+    pub fn ends_at(&self) -> DateTime<Utc> {
+        self.scheduled_date + Duration::minutes(self.runtime())
+    }
+
+    pub fn rewatched(&self) -> bool {
+        self.watch_log.len() > 1
+    }
+
+    // Moves a legacy watched flag in to the log, dated when the event was scheduled.
+    pub fn upgrade_watched(&mut self) {
+        if self.legacy_watched && self.watch_log.is_empty() {
+            self.watch_log.push(WatchRecord {
+                watched_at: self.scheduled_date,
+                rating: None,
+                notes: None,
+            });
+        }
+        self.legacy_watched = false;
+    }
 }
 
 // pub trait ShowEvent {
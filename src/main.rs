@@ -7,28 +7,73 @@ use yew::prelude::*;
 use yew::{html, Component, Context, Html};
 use yew_router::prelude::*;
 
+mod agenda;
+mod calendars;
+mod catch_up;
+mod episode_rules;
 mod episodes_picker;
 mod event_calendar;
 mod event_details;
+mod event_history;
 mod event_manager;
 mod events;
 mod find_show;
+mod new_episodes;
 mod schedule_show;
 mod search_client;
+mod share;
 mod show_card;
+mod show_progress;
 mod site_config;
+mod stats;
+mod tags;
+mod sync;
+mod time_grid;
+mod time_zone;
+mod tracked_shows;
 mod tv_card;
 mod ui_helpers;
+mod watchlist;
 mod datetime_picker;
 
-use crate::event_calendar::EventCalendar;
+use chrono::{NaiveDate, NaiveTime, Utc};
+use uuid::Uuid;
+
+use crate::agenda::Agenda;
+use crate::event_calendar::{CalendarView, EventCalendar};
+use crate::event_manager::EventManager;
 use crate::find_show::FindShow;
+use crate::share::SharedCalendar;
 use crate::site_config::{ByngerStore, SiteConfig};
+use crate::stats::Stats;
+use crate::sync::SyncPanel;
+use crate::time_zone::UserTimeZone;
+use crate::watchlist::Watchlist;
 
 #[derive(Routable, PartialEq, Eq, Clone, Debug)]
 pub enum Route {
     #[at("/")]
     Home,
+    #[at("/calendar/:year/:month")]
+    CalendarMonth { year: i32, month: u32 },
+    #[at("/calendar/:year/:month/:day")]
+    CalendarMonthDay { year: i32, month: u32, day: u32 },
+    #[at("/calendar/week/:date")]
+    CalendarWeek { date: NaiveDate },
+    #[at("/calendar/day/:date")]
+    CalendarDay { date: NaiveDate },
+    #[at("/event/:id")]
+    Event { id: Uuid },
+    #[at("/shared/:payload")]
+    Shared { payload: String },
+    #[at("/agenda")]
+    Agenda,
+    #[at("/stats")]
+    Stats,
+    #[at("/watchlist")]
+    Watchlist,
+    #[at("/sync")]
+    Sync,
     #[at("/schedule")]
     Schedule,
     #[at("/config")]
@@ -90,6 +135,10 @@ impl Component for Bynger {
                     <div class={classes!("navbar-menu", is_active)}>
                         <div class="navbar-start">
                             <Link<Route> to={Route::Home} classes="navbar-item">{ "Home" }</Link<Route>>
+                            <Link<Route> to={Route::Agenda} classes="navbar-item">{ "Agenda" }</Link<Route>>
+                            <Link<Route> to={Route::Watchlist} classes="navbar-item">{ "Watchlist" }</Link<Route>>
+                            <Link<Route> to={Route::Stats} classes="navbar-item">{ "Stats" }</Link<Route>>
+                            <Link<Route> to={Route::Sync} classes="navbar-item">{ "Sync" }</Link<Route>>
                             <Link<Route> to={Route::Schedule} classes="navbar-item">{ "Schedule" }</Link<Route>>
                             <Link<Route> to={Route::Config} classes="navbar-item">{ "Config" }</Link<Route>>
                         </div>
@@ -117,13 +166,47 @@ fn switch(routes: Route) -> Html {
 
     // Redirect to config if TMDB API Key doesn't exist or is empty.
     // Dont redirect if we're already going to config (otherwise infinite redirect)
-    if (api_key.is_err() || api_key.expect("").is_empty()) && routes != Route::Config {
+    // Shared schedules don't need TMDB, so friends without a key can still see them.
+    if (api_key.is_err() || api_key.expect("").is_empty())
+        && !matches!(routes, Route::Config | Route::Shared { .. })
+    {
         html! { <Redirect<Route> to={Route::Config}/> }
     } else {
         match routes {
             Route::Home => {
                 html! { <EventCalendar /> }
             }
+            Route::CalendarMonth { year, month } => {
+                calendar_page(CalendarView::Month, NaiveDate::from_ymd_opt(year, month, 1))
+            }
+            Route::CalendarMonthDay { year, month, day } => {
+                calendar_page(CalendarView::Month, NaiveDate::from_ymd_opt(year, month, day))
+            }
+            Route::CalendarWeek { date } => calendar_page(CalendarView::Week, Some(date)),
+            Route::CalendarDay { date } => calendar_page(CalendarView::Day, Some(date)),
+            Route::Event { id } => {
+                let date = EventManager::create()
+                    .events
+                    .iter()
+                    .find(|se| se.uuid == id)
+                    .map_or_else(Utc::now, |se| se.scheduled_date);
+                html! { <EventCalendar event={Some(id)} {date} /> }
+            }
+            Route::Shared { payload } => {
+                html! { <SharedCalendar {payload} /> }
+            }
+            Route::Agenda => {
+                html! { <Agenda /> }
+            }
+            Route::Stats => {
+                html! { <Stats /> }
+            }
+            Route::Watchlist => {
+                html! { <Watchlist /> }
+            }
+            Route::Sync => {
+                html! { <SyncPanel /> }
+            }
             Route::Schedule => {
                 html! { <FindShow /> }
             }
@@ -134,6 +217,18 @@ fn switch(routes: Route) -> Html {
     }
 }
 
+// Dates that don't exist (e.g. /calendar/2026/13) fall back to today.
+fn calendar_page(view: CalendarView, date: Option<NaiveDate>) -> Html {
+    let date = date.map_or_else(Utc::now, |d| {
+        UserTimeZone::to_utc(
+            &UserTimeZone::get(),
+            d.and_time(NaiveTime::from_hms_opt(0, 0, 1).unwrap()),
+        )
+    });
+
+    html! { <EventCalendar {view} {date} /> }
+}
+
 fn main() {
     yew::Renderer::<Bynger>::new().render();
 }
@@ -0,0 +1,237 @@
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::ops::Add;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use chrono::{Duration, Utc};
+use gloo::storage::{LocalStorage, Storage};
+use weblog::{console_error, console_log};
+use yew::prelude::*;
+
+use crate::calendars::DEFAULT_CALENDAR;
+use crate::event_manager::EventManager;
+use crate::events::ScheduledEvent;
+use crate::schedule_show::{distribute_episodes, Episode, SchedulingBoundaries};
+use crate::search_client::TMDB;
+use crate::site_config::ByngerStore;
+use crate::time_zone::UserTimeZone;
+use crate::tracked_shows::{TrackedShow, TrackedShowManager};
+
+// Tracked shows are only checked once per app start, not every time the calendar shows up.
+static CHECKED_THIS_LOAD: AtomicBool = AtomicBool::new(false);
+
+type Proposal = (TrackedShow, Vec<Episode>);
+
+// What's been found lives outside the component, so leaving the calendar and coming back
+// still shows anything not yet scheduled or dismissed.
+thread_local! {
+    static PROPOSALS: RefCell<Vec<Proposal>> = const { RefCell::new(vec![]) };
+}
+
+pub struct NewEpisodes;
+
+pub enum NewEpisodesMsg {
+    CheckTracked,
+    Found,
+    Schedule(usize),
+    Dismiss(usize),
+    Untrack(usize),
+}
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct NewEpisodesProps {
+    pub onscheduled: Callback<()>,
+}
+
+impl NewEpisodes {
+    fn take_proposal(show_id: usize) -> Option<Proposal> {
+        PROPOSALS.with(|proposals| {
+            let mut proposals = proposals.borrow_mut();
+            let pos = proposals.iter().position(|(ts, _)| ts.show_id == show_id)?;
+            Some(proposals.remove(pos))
+        })
+    }
+
+    // Puts back whatever's still to be scheduled or dismissed.
+    fn keep_proposal(tracked: TrackedShow, episodes: Vec<Episode>) {
+        if !episodes.is_empty() {
+            PROPOSALS.with(|proposals| proposals.borrow_mut().push((tracked, episodes)));
+        }
+    }
+}
+
+impl Component for NewEpisodes {
+    type Message = NewEpisodesMsg;
+    type Properties = NewEpisodesProps;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        if !CHECKED_THIS_LOAD.swap(true, Ordering::Relaxed) {
+            ctx.link().send_message(NewEpisodesMsg::CheckTracked);
+        }
+
+        Self
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            NewEpisodesMsg::CheckTracked => {
+                let api_key: String =
+                    LocalStorage::get(ByngerStore::TmdbApiKey.to_string()).unwrap_or_default();
+                if api_key.is_empty() {
+                    return false;
+                }
+                let search_client = TMDB::new(api_key);
+                let today = Utc::now().with_timezone(&UserTimeZone::get()).date_naive();
+                let scheduled: HashSet<usize> = EventManager::create()
+                    .events
+                    .iter()
+                    .filter_map(|se| se.episode.as_ref().map(|ep| ep.id))
+                    .collect();
+
+                for tracked in TrackedShowManager::create().shows {
+                    let search_client = search_client.clone();
+                    let scheduled = scheduled.clone();
+                    ctx.link().send_future(async move {
+                        let seasons = search_client
+                            .get_seasons_episodes(&tracked.show_id.to_string())
+                            .await
+                            .unwrap_or_default();
+                        let new_episodes = seasons
+                            .into_iter()
+                            .flat_map(|so| so.episodes)
+                            .map(|ep| {
+                                Episode::from_tmdb(
+                                    ep,
+                                    &tracked.show_name,
+                                    tracked.show_id,
+                                    tracked.fallback_runtime,
+                                )
+                            })
+                            .filter(|ep| ep.aired_on().is_some_and(|aired| aired <= today))
+                            .filter(|ep| {
+                                !tracked.known_episodes.contains(&ep.id) && !scheduled.contains(&ep.id)
+                            })
+                            .collect::<Vec<Episode>>();
+
+                        if !new_episodes.is_empty() {
+                            console_log!(format!(
+                                "BYNGER - {} new episodes of {}",
+                                new_episodes.len(),
+                                tracked.show_name
+                            ));
+                        }
+                        NewEpisodes::keep_proposal(tracked, new_episodes);
+
+                        NewEpisodesMsg::Found
+                    });
+                }
+
+                false
+            }
+            NewEpisodesMsg::Found => true,
+            NewEpisodesMsg::Schedule(show_id) => {
+                if let Some((tracked, episodes)) = Self::take_proposal(show_id) {
+                    let time_zone = UserTimeZone::get();
+                    let range_start = Utc::now().with_timezone(&time_zone);
+                    let range_end = range_start.add(Duration::weeks(4));
+                    let bounds = SchedulingBoundaries {
+                        start_date: range_start.date_naive(),
+                        start_time: range_start.time(),
+                        end_date: range_end.date_naive(),
+                        end_time: range_end.time(),
+                        time_zone,
+                    };
+
+                    match distribute_episodes(&episodes, &bounds, &tracked.options) {
+                        Ok(scheduled_events) => {
+                            let mut em = EventManager::create();
+                            // New episodes join the calendar the show was last scheduled in.
+                            let calendar = em
+                                .events
+                                .iter()
+                                .rev()
+                                .find(|se| se.episode.as_ref().map(|ep| ep.show_id) == Some(show_id))
+                                .map_or(DEFAULT_CALENDAR, |se| se.calendar);
+                            let scheduled_events = scheduled_events
+                                .into_iter()
+                                .map(|se| ScheduledEvent { calendar, ..se })
+                                .collect::<Vec<ScheduledEvent>>();
+                            // Only what actually made it on to the calendar is done with.
+                            let ids = scheduled_events
+                                .iter()
+                                .filter_map(|se| se.episode.as_ref().map(|ep| ep.id))
+                                .collect::<Vec<usize>>();
+                            let unscheduled = match em.add_events(scheduled_events) {
+                                Ok(_) => {
+                                    console_log!("BYNGER - Schedule Update Succeeded");
+                                    let _ = TrackedShowManager::create().mark_known(show_id, &ids);
+                                    episodes.into_iter().filter(|ep| !ids.contains(&ep.id)).collect()
+                                }
+                                Err(e) => {
+                                    console_log!(format!("BYNGER - Schedule Update Failed - {}", e));
+                                    episodes
+                                }
+                            };
+                            Self::keep_proposal(tracked, unscheduled);
+                            ctx.props().onscheduled.emit(());
+                        }
+                        Err(e) => {
+                            console_error!(e);
+                            Self::keep_proposal(tracked, episodes);
+                        }
+                    }
+                }
+
+                true
+            }
+            NewEpisodesMsg::Dismiss(show_id) => {
+                if let Some((_, episodes)) = Self::take_proposal(show_id) {
+                    let ids = episodes.iter().map(|ep| ep.id).collect::<Vec<usize>>();
+                    let _ = TrackedShowManager::create().mark_known(show_id, &ids);
+                }
+
+                true
+            }
+            NewEpisodesMsg::Untrack(show_id) => {
+                Self::take_proposal(show_id);
+                let _ = TrackedShowManager::create().untrack(show_id);
+
+                true
+            }
+        }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        PROPOSALS
+            .with(|proposals| proposals.borrow().clone())
+            .iter()
+            .map(|(tracked, episodes)| {
+                let show_id = tracked.show_id;
+                let runtime: usize = episodes.iter().map(|ep| ep.episode_run_time).sum();
+                let onschedule = ctx.link().callback(move |_| NewEpisodesMsg::Schedule(show_id));
+                let ondismiss = ctx.link().callback(move |_| NewEpisodesMsg::Dismiss(show_id));
+                let onuntrack = ctx.link().callback(move |_| NewEpisodesMsg::Untrack(show_id));
+
+                html! {
+                    <div class="notification is-info new-episodes">
+                        <button class="delete" aria-label="dismiss" onclick={&ondismiss}></button>
+                        <p>
+                            <strong>{&tracked.show_name}</strong>
+                            {format!(" has {} new episode(s) ({} min) since you last scheduled it.",
+                                episodes.len(), runtime)}
+                        </p>
+                        <div class="buttons mt-1">
+                            <button class="button is-small is-success" onclick={onschedule}>
+                                {"Schedule With Last Options"}
+                            </button>
+                            <button class="button is-small" onclick={ondismiss}>{"Ignore These"}</button>
+                            <button class="button is-small is-danger is-outlined" onclick={onuntrack}>
+                                {"Stop Tracking"}
+                            </button>
+                        </div>
+                    </div>
+                }
+            })
+            .collect::<Html>()
+    }
+}
@@ -1,9 +1,8 @@
-use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Timelike, Utc};
+use chrono_tz::Tz;
 
 use std::collections::HashMap;
-use std::ops::Add;
-
-use std::str::FromStr;
+use std::ops::{Add, Sub};
 
 use gloo::storage::{LocalStorage, Storage};
 use wasm_bindgen::prelude::wasm_bindgen;
@@ -12,19 +11,22 @@ use wasm_bindgen::JsValue;
 use weblog::{console_error, console_log};
 use yew::prelude::*;
 
-use crate::episodes_picker::EpisodePicker;
+use crate::calendars::{calendar_select, selected_calendar, Calendars, DEFAULT_CALENDAR};
+use crate::episode_rules::EpisodeRules;
+use crate::episodes_picker::{EpisodePicker, EpisodeSelection};
 use crate::event_calendar::CalendarSchedulableEvent;
 use crate::event_manager::EventManager;
 use crate::events::ScheduledEvent;
 
-use crate::search_client::{MediaType, TMDB};
+use crate::search_client::{MediaType, TMDBEpisodeObj, TMDB};
 use crate::show_card::Show;
 use crate::site_config::ByngerStore;
+use crate::time_zone::UserTimeZone;
+use crate::tracked_shows::{TrackedShow, TrackedShowManager};
 use crate::ui_helpers::UiHelpers;
 
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
-use web_sys::HtmlElement;
 
 #[wasm_bindgen(module = "/js/helpers.js")]
 extern "C" {
@@ -41,13 +43,256 @@ pub struct SchedulingBoundaries {
     pub start_time: NaiveTime,
     pub end_date: NaiveDate,
     pub end_time: NaiveTime,
+    pub time_zone: Tz, // The zone all of the above (and the day windows) are in.
 }
 
-#[derive(Clone, PartialEq, Eq)]
+// 0 = Monday ... 6 = Sunday.
+// Mirrors: https://docs.rs/chrono/latest/chrono/enum.Weekday.html#method.num_days_from_monday
+pub const DAYS_OF_WEEK: [&str; 7] = [
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+    "Sunday",
+];
+
+// The viewing window for a single day of the week.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DayWindow {
+    pub enabled: bool,
+    pub start_time: NaiveTime,
+    pub end_time: NaiveTime,
+    pub eps_per_day: usize, // 0 = Fill the window.
+}
+
+impl DayWindow {
+    // An end time at or before the start time wraps past midnight, e.g. 22:00 - 01:00.
+    pub fn crosses_midnight(&self) -> bool {
+        self.end_time <= self.start_time
+    }
+
+    // Start and end of the window as a single session beginning on the given date.
+    pub fn session(&self, date: NaiveDate) -> (NaiveDateTime, NaiveDateTime) {
+        let end_date = if self.crosses_midnight() {
+            date.add(Duration::days(1))
+        } else {
+            date
+        };
+
+        (
+            NaiveDateTime::new(date, self.start_time),
+            NaiveDateTime::new(end_date, self.end_time),
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SchedulingOptions {
-    pub days_of_week: HashMap<u32, bool>,
-    pub eps_per_day: usize,
+    pub days_of_week: HashMap<u32, DayWindow>,
     pub use_end_date: bool, // UI shows this in boundary limits.
+    pub gap_minutes: usize,        // Padding between episodes.
+    pub round_to_minutes: usize,   // Round start times up to the next 5/15/30 minutes, 0 = Off.
+    pub long_break_every: usize,   // Take a longer break every N episodes, 0 = Never.
+    pub long_break_minutes: usize,
+    pub air_date_mode: AirDateMode,
+}
+
+// How an episode's air date limits when it can be scheduled, for shows that are still airing.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AirDateMode {
+    Ignore,
+    // Never before the episode airs, optionally waiting some days after.
+    NotBeforeAirDate { delay_days: usize },
+    // Each episode lands on the chosen day of the week following its air date (plus delay).
+    FollowAlong { weekday: u32, delay_days: usize },
+}
+
+impl SchedulingOptions {
+    // The window for the given date, if that day of the week is available.
+    pub fn window(&self, date: NaiveDate) -> Option<&DayWindow> {
+        let weekday = date.weekday().num_days_from_monday();
+        match self.air_date_mode {
+            // Following along only ever uses the chosen day, whichever days are ticked.
+            AirDateMode::FollowAlong {
+                weekday: follow_day,
+                ..
+            } => self
+                .days_of_week
+                .get(&follow_day)
+                .filter(|_| weekday == follow_day),
+            _ => self.days_of_week.get(&weekday).filter(|w| w.enabled),
+        }
+    }
+
+    // Earliest date an episode may be scheduled on; None when it can't be placed yet.
+    fn not_before(&self, ep: &Episode) -> Option<Option<NaiveDate>> {
+        match self.air_date_mode {
+            AirDateMode::Ignore => Some(None),
+            AirDateMode::NotBeforeAirDate { delay_days }
+            | AirDateMode::FollowAlong { delay_days, .. } => ep
+                .aired_on()
+                .map(|aired| Some(aired.add(Duration::days(delay_days as i64)))),
+        }
+    }
+}
+
+// Rounds up to the next multiple of `step` minutes past midnight; seconds are dropped.
+fn round_up_minutes(datetime: NaiveDateTime, step: usize) -> NaiveDateTime {
+    let datetime = datetime.with_nanosecond(0).unwrap_or(datetime);
+    if step == 0 {
+        return datetime;
+    }
+
+    let step = step as i64 * 60;
+    let remainder = datetime.num_seconds_from_midnight() as i64 % step;
+    if remainder == 0 {
+        datetime
+    } else {
+        datetime.add(Duration::seconds(step - remainder))
+    }
+}
+
+// Lays out episodes, in order, across the enabled day windows between the scheduling boundaries.
+pub fn distribute_episodes(
+    episodes: &[Episode],
+    bounds: &SchedulingBoundaries,
+    options: &SchedulingOptions,
+) -> Result<Vec<ScheduledEvent>, String> {
+    let any_window = (0..7).any(|n| {
+        options
+            .window(bounds.start_date.add(Duration::days(n)))
+            .is_some()
+    });
+    if !any_window {
+        return Err("No days of the week are enabled for scheduling.".to_string());
+    }
+
+    let longest_window = (0..7)
+        .filter_map(|n| {
+            let day = bounds.start_date.add(Duration::days(n));
+            options.window(day).map(|w| {
+                let (start, end) = w.session(day);
+                end - start
+            })
+        })
+        .max()
+        .unwrap_or_else(Duration::zero);
+
+    let lower_datetime = NaiveDateTime::new(bounds.start_date, bounds.start_time);
+    let upper_datetime = NaiveDateTime::new(bounds.end_date, bounds.end_time);
+    // Beginning of a day's window, but never before our lower boundary.
+    let day_start = |day: NaiveDate| {
+        let start = options
+            .window(day)
+            .map(|w| w.session(day).0)
+            .unwrap_or_else(|| NaiveDateTime::new(day, NaiveTime::MIN));
+        start.max(lower_datetime)
+    };
+
+    // Start the day before, a late night window from then may still be open at our lower boundary.
+    let mut day = bounds.start_date.sub(Duration::days(1));
+    let mut curr_date = day_start(day);
+    let mut per_day = 0; // Counts per session, even when the session runs past midnight.
+
+    let mut scheduled_events = Vec::<ScheduledEvent>::new();
+    for ep in episodes {
+        // Hold episodes back until they've aired.
+        match options.not_before(ep) {
+            None => {
+                console_log!(format!(
+                    "BYNGER - Skipping s{}e{}, it has no air date yet.",
+                    ep.season_number, ep.episode_number
+                ));
+                continue;
+            }
+            Some(Some(aired)) => {
+                let aired_datetime = NaiveDateTime::new(aired, NaiveTime::MIN);
+                if curr_date < aired_datetime {
+                    // The day before, in case its session runs past midnight.
+                    let aired_day = aired.sub(Duration::days(1));
+                    if aired_day > day {
+                        day = aired_day;
+                        per_day = 0;
+                    }
+                    curr_date = aired_datetime;
+                }
+            }
+            Some(None) => {}
+        }
+
+        // It has to fit in a window somewhere, or we'd be looking for one forever.
+        let runtime = Duration::minutes(ep.episode_run_time as i64);
+        if runtime > longest_window {
+            return Err(format!(
+                "s{}e{} is longer than any viewing window.",
+                ep.season_number, ep.episode_number
+            ));
+        }
+
+        // Advance over any day not available to schedule, or whose window is used up.
+        loop {
+            curr_date = round_up_minutes(curr_date, options.round_to_minutes);
+            match options.window(day) {
+                Some(w)
+                    if curr_date.add(runtime) <= w.session(day).1
+                        && (w.eps_per_day == 0 || per_day < w.eps_per_day) =>
+                {
+                    break
+                }
+                _ => {
+                    day = day.add(Duration::days(1));
+                    // Never step backwards, the last session may have run in to this one.
+                    curr_date = day_start(day).max(curr_date);
+                    per_day = 0;
+                }
+            }
+        }
+
+        if options.use_end_date && curr_date > upper_datetime {
+            return Err(format!(
+                "Only {} of {} episodes fit before the end date.",
+                scheduled_events.len(),
+                episodes.len()
+            ));
+        }
+
+        // Push our current episode with the available date
+        scheduled_events.push(ScheduledEvent {
+            uuid: Uuid::new_v4(),
+            scheduled_date: UserTimeZone::to_utc(&bounds.time_zone, curr_date),
+            media_type: ep.media_type(),
+            episode: Some(ep.to_owned()),
+            movie: None,
+            watch_log: vec![],
+            legacy_watched: false,
+            calendar: DEFAULT_CALENDAR,
+            party: None,
+            tags: vec![],
+        });
+
+        // Advance our currently schedulable datetime by the episode's length plus any padding
+        curr_date = curr_date.add(Duration::minutes(
+            (ep.episode_run_time + options.gap_minutes) as i64,
+        ));
+        // increment out per day.
+        per_day += 1;
+        if options.long_break_every > 0 && per_day % options.long_break_every == 0 {
+            curr_date = curr_date.add(Duration::minutes(options.long_break_minutes as i64));
+        }
+    }
+
+    // We should always schedule all eps, unless they're waiting on an air date.
+    if options.air_date_mode == AirDateMode::Ignore && scheduled_events.len() != episodes.len() {
+        return Err(format!(
+            "Only {} of {} episodes could be scheduled.",
+            scheduled_events.len(),
+            episodes.len()
+        ));
+    }
+
+    Ok(scheduled_events)
 }
 
 // FIXME: These structs should probably get moved in to a search_client as generic output types.
@@ -74,6 +319,40 @@ pub struct Movie {
     pub runtime: usize,
 }
 
+impl Episode {
+    pub fn from_tmdb(
+        ep: TMDBEpisodeObj,
+        show_name: &str,
+        show_id: usize,
+        fuzzy_runtime_max: usize,
+    ) -> Self {
+        // If the episode comes with a runtime, use that, otherwise use the max length from the show level array.
+        // not exact and relies on an undocumented field returned from the API.
+        // TODO: In the the future a user-fudgable option for setting a default value would be nice.
+        let fuzzy_runtime = match ep.runtime {
+            None => fuzzy_runtime_max,
+            Some(runtime) => runtime,
+        };
+
+        Episode {
+            air_date: ep.air_date.unwrap_or_else(|| String::from("unknown")),
+            episode_number: ep.episode_number,
+            name: ep.name,
+            id: ep.id,
+            season_number: ep.season_number,
+            still_path: ep.still_path,
+            episode_run_time: fuzzy_runtime,
+            show_name: show_name.to_string(),
+            show_id,
+        }
+    }
+
+    // TMDB air dates are YYYY-MM-DD, or missing for episodes that haven't been announced.
+    pub fn aired_on(&self) -> Option<NaiveDate> {
+        NaiveDate::parse_from_str(&self.air_date, "%Y-%m-%d").ok()
+    }
+}
+
 impl CalendarSchedulableEvent for Episode {
     fn id(&self) -> String {
         self.id.to_string()
@@ -125,10 +404,13 @@ pub struct ScheduleShow {
     show: Option<Show>,
     seasons: Option<Vec<Season>>,
     episodes_to_schedule: Vec<Episode>,
+    selected_episodes: EpisodeSelection,
     node_ref: NodeRef,
     schedule_show_state: ScheduleShowState,
     search_client: TMDB,
     range_picker: Option<JsValue>,
+    // Read once for the calendar picker, nothing in here can change them.
+    calendars: Calendars,
 }
 
 #[derive(Clone, PartialEq, Properties)]
@@ -136,6 +418,12 @@ pub struct ScheduleProps {
     pub show_id: String,
     pub media_type: MediaType,
     pub on_cancel: Callback<MouseEvent>,
+    // Already scheduled events to lay out again instead of picking new episodes.
+    #[prop_or_default]
+    pub remaining: Option<Vec<ScheduledEvent>>,
+    // Fires once events have actually been saved, before the modal closes.
+    #[prop_or_default]
+    pub on_scheduled: Callback<()>,
 }
 
 pub enum ScheduleShowMsg {
@@ -145,6 +433,7 @@ pub enum ScheduleShowMsg {
     FetchSeasons,
     ShowResult(Show),
     SeasonsResult(Vec<Season>),
+    SelectEpisodes(usize, EpisodeSelection),
     ScheduleEpisodes(Vec<Episode>),
     DistributeEpisodes(SchedulingBoundaries, SchedulingOptions),
     DistributeMovie((NaiveDate, NaiveTime)),
@@ -163,10 +452,12 @@ impl Component for ScheduleShow {
             show: None,
             seasons: None,
             episodes_to_schedule: Vec::<Episode>::new(),
+            selected_episodes: EpisodeSelection::new(),
             node_ref: NodeRef::default(),
             schedule_show_state: ScheduleShowState::default(),
             search_client: TMDB::new(api_key),
             range_picker: None,
+            calendars: Calendars::load(),
         }
     }
 
@@ -220,34 +511,18 @@ impl Component for ScheduleShow {
                                     let seasons = s.into_iter().fold(
                                         Vec::<Season>::new(),
                                         |mut seasons, so| {
-                                            let eps = so.episodes.into_iter().fold(
-                                                Vec::<Episode>::new(),
-                                                |mut eps, ep| {
-                                                    // If the episode comes with a runtime, use that, otherwise use the max length from the show level array.
-                                                    // not exact and relies on an undocumented field returned from the API.
-                                                    // TODO: In the the future a user-fudgable option for setting a default value would be nice.
-                                                    let fuzzy_runtime = match ep.runtime {
-                                                        None => fuzzy_runtime_max,
-                                                        Some(runtime) => runtime,
-                                                    };
-                                                    eps.push(Episode {
-                                                        air_date: ep.air_date.unwrap_or_else(
-                                                            || String::from("unknown"),
-                                                        ),
-                                                        episode_number: ep.episode_number,
-                                                        name: ep.name,
-                                                        id: ep.id,
-                                                        season_number: ep.season_number,
-                                                        still_path: ep.still_path,
-                                                        episode_run_time: fuzzy_runtime,
-                                                        show_name: show.clone().title.unwrap(),
-                                                        show_id: show.id.parse().unwrap(),
-                                                    });
-
-                                                    // console_log!(format!("ep {}; runtime: {}.", ep.episode_number, fuzzy_runtime));
-                                                    eps
-                                                },
-                                            );
+                                            let eps = so
+                                                .episodes
+                                                .into_iter()
+                                                .map(|ep| {
+                                                    Episode::from_tmdb(
+                                                        ep,
+                                                        show.title.as_ref().unwrap(),
+                                                        show.id.parse().unwrap(),
+                                                        fuzzy_runtime_max,
+                                                    )
+                                                })
+                                                .collect::<Vec<Episode>>();
 
                                             seasons.push(Season {
                                                 id: so.id,
@@ -263,6 +538,10 @@ impl Component for ScheduleShow {
                                         },
                                     );
 
+                                    // Specials (season 0) go to the back of the list.
+                                    let mut seasons = seasons;
+                                    seasons.sort_by_key(|s| (s.season_number == 0, s.season_number));
+
                                     ScheduleShowMsg::SeasonsResult(seasons)
                                 }
                             }
@@ -276,10 +555,20 @@ impl Component for ScheduleShow {
                 self.show = Some(show.clone());
 
                 match show.media_type {
-                    MediaType::tv => {
-                        ctx.link()
-                            .send_future(async move { ScheduleShowMsg::FetchSeasons });
-                    }
+                    MediaType::tv => match &ctx.props().remaining {
+                        // Redistributing skips straight past the episode picker.
+                        Some(remaining) => {
+                            let episodes = remaining
+                                .iter()
+                                .filter_map(|se| se.episode.clone())
+                                .collect::<Vec<Episode>>();
+                            ctx.link().send_message(ScheduleShowMsg::ScheduleEpisodes(episodes));
+                        }
+                        None => {
+                            ctx.link()
+                                .send_future(async move { ScheduleShowMsg::FetchSeasons });
+                        }
+                    },
                     MediaType::movie => {
                         self.schedule_show_state = ScheduleShowState::MovieScheduler;
                     }
@@ -299,6 +588,12 @@ impl Component for ScheduleShow {
                 console_error!(e);
                 false
             }
+            ScheduleShowMsg::SelectEpisodes(season_number, selection) => {
+                self.selected_episodes.retain(|(s, _)| *s != season_number);
+                self.selected_episodes.extend(selection);
+
+                true
+            }
             ScheduleShowMsg::ScheduleEpisodes(eps) => {
                 self.episodes_to_schedule = eps;
                 self.schedule_show_state = ScheduleShowState::EpisodeScheduler;
@@ -306,79 +601,96 @@ impl Component for ScheduleShow {
                 true
             }
             ScheduleShowMsg::DistributeEpisodes(bounds, options) => {
-                // Start at the beginning
-                let mut curr_date = NaiveDateTime::new(bounds.start_date, bounds.start_time);
-                let mut per_day = 0;
-                let upper_datetime = NaiveDateTime::new(bounds.end_date, bounds.end_time);
-
-                let scheduled_events = self.episodes_to_schedule.iter().fold(
-                    Vec::<ScheduledEvent>::new(),
-                    |mut scheduled_events, ep| {
-                        // Check days_of_week and advance over any day not available to schedule.
-                        while !options
-                            .days_of_week
-                            .get(&(curr_date.weekday().num_days_from_monday()))
-                            .unwrap()
-                        {
-                            curr_date = NaiveDateTime::new(
-                                curr_date.add(Duration::days(1)).date(),
-                                bounds.start_time,
-                            );
-                            per_day = 0;
-                        }
-
-                        if options.use_end_date && curr_date > upper_datetime {
-                            // TODO: Error if they're attempting to schedule more episodes than their parameters can accommodate
-                        }
-
-                        // Push our current episode with the available date
-                        scheduled_events.push(ScheduledEvent {
-                            uuid: Uuid::new_v4(),
-                            scheduled_date: DateTime::from_utc(curr_date, Utc),
-                            media_type: ep.media_type(),
-                            episode: Some(ep.to_owned()),
-                            movie: None,
-                            watched: false,
-                        });
-
-                        // Advance our currently schedulable datetime by the episode's length
-                        curr_date = curr_date.add(Duration::minutes(ep.episode_run_time as i64));
-                        // increment out per day.
-                        per_day += 1;
-
-                        // if we've reached our max eps per day or advanced the time beyond the upper time boundary
-                        // then advance the day and reset the counter
-                        if per_day == options.eps_per_day || curr_date.time() > bounds.end_time {
-                            curr_date = NaiveDateTime::new(
-                                curr_date.add(Duration::days(1)).date(),
-                                bounds.start_time,
-                            );
-                            per_day = 0;
+                let scheduled_events =
+                    match distribute_episodes(&self.episodes_to_schedule, &bounds, &options) {
+                        Ok(scheduled_events) => scheduled_events,
+                        Err(e) => {
+                            console_error!(e);
+                            return false;
                         }
+                    };
 
-                        // Fold
-                        scheduled_events
-                    },
-                );
-
-                // We should always schedule all eps.
-                assert_eq!(&self.episodes_to_schedule.len(), &scheduled_events.len());
-
-                // console_log!(format!("{} - {}", se.scheduled_date, se.event.name() ));
-                // scheduled_events.into_iter().for_each(|se| {
-                //     console_info!(format!("{} - {} - {}", se.scheduled_date.weekday(), se.scheduled_date, se.event.name() ));
-                // });
-
+                let scheduled_ids = scheduled_events
+                    .iter()
+                    .filter_map(|se| se.episode.as_ref().map(|ep| ep.id))
+                    .collect::<Vec<usize>>();
                 let mut em = EventManager::create();
+                let remaining = ctx.props().remaining.clone();
+                let result = match &remaining {
+                    // Only the dates move, the events (and their tags, parties, etc.) are the same ones.
+                    // Anything that couldn't be placed, e.g. still waiting on an air date, stays where it was.
+                    Some(remaining) => {
+                        let mut originals = remaining.clone();
+                        let moved = scheduled_events
+                            .into_iter()
+                            .filter_map(|se| {
+                                let ep_id = se.episode.as_ref().map(|ep| ep.id);
+                                let pos = originals
+                                    .iter()
+                                    .position(|o| o.episode.as_ref().map(|ep| ep.id) == ep_id)?;
+                                Some(ScheduledEvent {
+                                    scheduled_date: se.scheduled_date,
+                                    ..originals.remove(pos)
+                                })
+                            })
+                            .collect::<Vec<ScheduledEvent>>();
+                        let replaced = moved.iter().map(|se| se.uuid).collect::<Vec<Uuid>>();
+                        em.replace_events(&replaced, moved)
+                    }
+                    None => {
+                        let calendar = selected_calendar(&self.calendars, "#scheduleCalendar");
+                        let scheduled_events = scheduled_events
+                            .into_iter()
+                            .map(|se| ScheduledEvent { calendar, ..se })
+                            .collect::<Vec<ScheduledEvent>>();
+                        em.add_events(scheduled_events)
+                    }
+                };
 
-                match em.add_events(scheduled_events) {
+                match result {
                     Ok(_) => {
                         self.schedule_show_state = ScheduleShowState::Loading;
                         console_log!("BYNGER - Schedule Update Succeeded");
+                        ctx.props().on_scheduled.emit(());
                     }
                     Err(e) => console_log!(format!("BYNGER - Schedule Update Failed - {}", e)),
                 }
 
+                // Shows still airing get tracked so we can offer up new episodes as they air.
+                // Redistributing didn't look at the show's episodes, so leave tracking as it was.
+                if let Some(show) = self
+                    .show
+                    .as_ref()
+                    .filter(|s| s.in_production && remaining.is_none())
+                {
+                    // Whatever had aired was there to pick from, anything still to come (or without
+                    // an air date yet) stays unknown so it's offered once it airs.
+                    let today = Utc::now().with_timezone(&bounds.time_zone).date_naive();
+                    let known_episodes = self
+                        .seasons
+                        .iter()
+                        .flatten()
+                        .flat_map(|s| s.episodes.iter().flatten())
+                        .filter(|ep| ep.aired_on().is_some_and(|aired| aired <= today))
+                        .map(|ep| ep.id)
+                        .chain(scheduled_ids)
+                        .collect();
+                    let tracked = TrackedShow {
+                        show_id: show.id.parse().unwrap(),
+                        show_name: show.title.clone().unwrap_or_default(),
+                        fallback_runtime: show
+                            .episode_run_time
+                            .as_ref()
+                            .and_then(|rt| rt.iter().max().copied())
+                            .unwrap_or(60),
+                        options,
+                        known_episodes,
+                    };
+                    if let Err(e) = TrackedShowManager::create().track(tracked) {
+                        console_error!(format!("BYNGER - Tracking Show Failed - {}", e));
+                    }
+                }
+
                 // Close our modal by re-using the on_cancel emitter by faking a mouse click.
                 // Hacky but effective.
                 ctx.props()
@@ -389,8 +701,10 @@ impl Component for ScheduleShow {
             }
             ScheduleShowMsg::DistributeMovie(datetime) => {
                 let show = self.show.clone().unwrap();
-                let scheduled_date =
-                    DateTime::from_utc(NaiveDateTime::new(datetime.0, datetime.1), Utc);
+                let scheduled_date = UserTimeZone::to_utc(
+                    &UserTimeZone::get(),
+                    NaiveDateTime::new(datetime.0, datetime.1),
+                );
                 let movie = Movie {
                     release_date: show
                         .first_air_date
@@ -413,7 +727,11 @@ impl Component for ScheduleShow {
                     media_type: self.show.clone().unwrap().media_type,
                     episode: None,
                     movie: Some(movie),
-                    watched: false,
+                    watch_log: vec![],
+                    legacy_watched: false,
+                    calendar: selected_calendar(&self.calendars, "#scheduleCalendar"),
+                    party: None,
+                    tags: vec![],
                 }]);
 
                 let mut em = EventManager::create();
@@ -421,6 +739,7 @@ impl Component for ScheduleShow {
                     Ok(_) => {
                         self.schedule_show_state = ScheduleShowState::Loading;
                         console_log!("BYNGER - Schedule Update Succeeded");
+                        ctx.props().on_scheduled.emit(());
                     }
                     Err(e) => console_log!(format!("BYNGER - Schedule Update Failed - {}", e)),
                 }
@@ -435,67 +754,23 @@ impl Component for ScheduleShow {
     }
 
     fn view(&self, ctx: &Context<Self>) -> Html {
-        let doc = gloo_utils::document();
         let seasons = self.seasons.clone();
         let props = ctx.props().clone();
         let _media_type = props.media_type;
         let on_cancel = move |e| props.on_cancel.emit(e);
+        let selected_episodes = self.selected_episodes.clone();
         let on_distribute = ctx.link().callback(move |_| {
-            // Bit of a brute force solution. I doubt its terribly performant, but I also doubt
-            // anyone would actually notice given the scale we're working in.
-            // Bucket-of-Eps
-            let mut episodes_to_schedule: Vec<Episode> = vec![];
-
-            // Parse through All
-            if let Ok(res) = doc.query_selector_all("li.is-active [id$='tab_all']") {
-                (0..=res.length()).into_iter().for_each(|r| {
-                    if let Some(node) = res.get(r) {
-                        // Feels like there should be a better way...what am I not understanding?
-                        let tab = (HtmlElement::from(JsValue::from(node))).id();
-                        let season_num: Vec<&str> = tab.split('_').collect();
-                        if let Some(s) = season_num.get(1) {
-                            if let Ok(season_as_usize) = usize::from_str(s) {
-                                if let Some(seas) = &seasons {
-                                    if let Some(found_season) =
-                                        seas.iter().find(|&p| p.season_number == season_as_usize)
-                                    {
-                                        episodes_to_schedule.append(
-                                            &mut found_season.episodes.as_ref().unwrap().clone(),
-                                        );
-                                    }
-                                }
-                            }
-                        }
-                    }
-                });
-            }
-
-            // Parse through the individually selected episodes
-            if let Ok(res) = doc.query_selector_all(".episode_checkbox:checked") {
-                (0..=res.length()).into_iter().for_each(|r| {
-                    if let Some(node) = res.get(r) {
-                        let ep_id = (HtmlElement::from(JsValue::from(node))).id();
-                        let split_id: Vec<&str> = ep_id.split('_').collect(); // format: season_07_episode_14
-                        if let (Some(sea_num), Some(ep_num)) = (split_id.get(1), split_id.get(3)) {
-                            if let (Ok(snum), Ok(enum_)) =
-                                (usize::from_str(sea_num), usize::from_str(ep_num))
-                            {
-                                if let Some(seas) = &seasons {
-                                    if let Some(s) = seas.iter().find(|s| s.season_number == snum) {
-                                        if let Some(eps) = &s.episodes {
-                                            if let Some(ep) =
-                                                eps.iter().find(|e| e.episode_number == enum_)
-                                            {
-                                                episodes_to_schedule.push(ep.clone());
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
-                });
-            }
+            // Seasons are already in scheduling order (specials last), so keep to that.
+            let episodes_to_schedule = seasons
+                .iter()
+                .flatten()
+                .flat_map(|s| s.episodes.iter().flatten())
+                .filter(|ep| selected_episodes.contains(&(ep.season_number, ep.episode_number)))
+                .cloned()
+                .collect::<Vec<Episode>>();
+
+            // Narrow the bucket down with whatever selection rules were set.
+            let episodes_to_schedule = EpisodeRules::from_inputs().apply(episodes_to_schedule);
 
             // Schedule our bucket of episodes
             ScheduleShowMsg::ScheduleEpisodes(episodes_to_schedule)
@@ -521,55 +796,61 @@ impl Component for ScheduleShow {
                     .expect("Bad end date format."),
                 end_time: NaiveTime::parse_from_str(&raw_end_time, "%H:%M")
                     .expect("Bad end time format."),
+                time_zone: UserTimeZone::get(),
             };
 
-            // 0 = Monday ... 6 = Sunday.
-            // Mirrors: https://docs.rs/chrono/latest/chrono/enum.Weekday.html#method.num_days_from_monday
-            let dows = HashMap::from([
-                (
-                    0,
-                    UiHelpers::get_value_from_checkbox_by_id("#checkbox_dow_Monday")
-                        .expect("Missing dow"),
-                ),
-                (
-                    1,
-                    UiHelpers::get_value_from_checkbox_by_id("#checkbox_dow_Tuesday")
-                        .expect("Missing dow"),
-                ),
-                (
-                    2,
-                    UiHelpers::get_value_from_checkbox_by_id("#checkbox_dow_Wednesday")
-                        .expect("Missing dow"),
-                ),
-                (
-                    3,
-                    UiHelpers::get_value_from_checkbox_by_id("#checkbox_dow_Thursday")
-                        .expect("Missing dow"),
-                ),
-                (
-                    4,
-                    UiHelpers::get_value_from_checkbox_by_id("#checkbox_dow_Friday")
-                        .expect("Missing dow"),
-                ),
-                (
-                    5,
-                    UiHelpers::get_value_from_checkbox_by_id("#checkbox_dow_Saturday")
-                        .expect("Missing dow"),
-                ),
-                (
-                    6,
-                    UiHelpers::get_value_from_checkbox_by_id("#checkbox_dow_Sunday")
-                        .expect("Missing dow"),
-                ),
-            ]);
-            let raw_epd = UiHelpers::get_value_from_input_by_id("#episodesPerDay")
-                .expect("Missing Eps Per Day?");
+            let dows = DAYS_OF_WEEK
+                .into_iter()
+                .enumerate()
+                .map(|(idx, day)| {
+                    let raw_start = UiHelpers::get_value_from_input_by_id(&format!("#pickerTimeStart_{day}"))
+                        .expect("Missing day start time?");
+                    let raw_end = UiHelpers::get_value_from_input_by_id(&format!("#pickerTimeEnd_{day}"))
+                        .expect("Missing day end time?");
+                    let raw_epd = UiHelpers::get_value_from_input_by_id(&format!("#episodesPerDay_{day}"))
+                        .expect("Missing Eps Per Day?");
+                    let window = DayWindow {
+                        enabled: UiHelpers::get_value_from_checkbox_by_id(&format!("#checkbox_dow_{day}"))
+                            .expect("Missing dow"),
+                        start_time: NaiveTime::parse_from_str(&raw_start, "%H:%M")
+                            .expect("Bad day start time format."),
+                        end_time: NaiveTime::parse_from_str(&raw_end, "%H:%M")
+                            .expect("Bad day end time format."),
+                        eps_per_day: raw_epd.parse::<usize>().expect("Bad eps per day value"),
+                    };
+
+                    (idx as u32, window)
+                })
+                .collect::<HashMap<u32, DayWindow>>();
             let raw_ued = UiHelpers::get_value_from_checkbox_by_id("#checkbox_use_end_date")
                 .expect("Missing use end date.");
+            let minutes_from_input = |id: &str| {
+                UiHelpers::get_value_from_input_by_id(id)
+                    .and_then(|raw| raw.parse::<usize>().ok())
+                    .unwrap_or(0)
+            };
+            let air_date_delay = minutes_from_input("#airDateDelay");
+            let air_date_mode = match UiHelpers::get_value_from_input_by_id("#airDateMode")
+                .unwrap_or_default()
+                .as_str()
+            {
+                "air_date" => AirDateMode::NotBeforeAirDate {
+                    delay_days: air_date_delay,
+                },
+                "follow_along" => AirDateMode::FollowAlong {
+                    weekday: minutes_from_input("#followAlongDay") as u32,
+                    delay_days: air_date_delay,
+                },
+                _ => AirDateMode::Ignore,
+            };
             let schedule_options = SchedulingOptions {
                 days_of_week: dows,
-                eps_per_day: raw_epd.parse::<usize>().expect("Bad eps per day value"),
                 use_end_date: raw_ued,
+                gap_minutes: minutes_from_input("#gapMinutes"),
+                round_to_minutes: minutes_from_input("#roundStartTimes"),
+                long_break_every: minutes_from_input("#longBreakEvery"),
+                long_break_minutes: minutes_from_input("#longBreakMinutes"),
+                air_date_mode,
             };
 
             ScheduleShowMsg::DistributeEpisodes(schedule_bounds, schedule_options)
@@ -591,6 +872,21 @@ impl Component for ScheduleShow {
             ScheduleShowMsg::DistributeMovie(datetime_tuple)
         });
 
+        let on_select = ctx.link().callback(|(season_number, selection)| {
+            ScheduleShowMsg::SelectEpisodes(season_number, selection)
+        });
+        let (selected_count, selected_runtime) = self
+            .seasons
+            .iter()
+            .flatten()
+            .flat_map(|s| s.episodes.iter().flatten())
+            .filter(|ep| self.selected_episodes.contains(&(ep.season_number, ep.episode_number)))
+            .fold((0, 0), |(count, runtime), ep| (count + 1, runtime + ep.episode_run_time));
+
+        // New events go in the calendar being looked at, redistributing keeps a show where it was.
+        let calendar = self.calendars.active;
+        let redistributing = ctx.props().remaining.is_some();
+
         let mut title = "Loading...".to_string();
         let mut subtitle = "".to_string();
         let date_format = "%F"; // YYYY-MM-DD
@@ -617,6 +913,11 @@ impl Component for ScheduleShow {
                             let overview_max_len = 300;
                             let _season_id = s.id;
                             let season_number = s.season_number;
+                            let season_title = if season_number == 0 {
+                                String::from("Specials")
+                            } else {
+                                format!("Season {}", &season_number)
+                            };
                             let episode_count = s.episodes.clone().unwrap().len();
                             let poster_fragment = UiHelpers::get_thumbnail(s.poster_path.clone());
                             let overview = s.overview.clone().unwrap_or_else(|| String::from("No Overview"));
@@ -632,7 +933,7 @@ impl Component for ScheduleShow {
                                             </div>
                                             <div class="media-content mb-0 pb-0">
                                                 <container class="container box pt-1 pb-1 pl-1 pr-1 mr-2">
-                                                    <h1 class="title is-4">{format!{"Season {}", &season_number}}</h1>
+                                                    <h1 class="title is-4">{season_title}</h1>
                                                     <h3 class="subtitle is-6 mb-1">
                                                         <div>{format!{"Episodes: {}", &episode_count}}</div>
                                                         <div>{format!{"First Aired: {}", &air_date}}</div>
@@ -651,7 +952,7 @@ impl Component for ScheduleShow {
                                             </div>
                                         </div>
                                         <div class="content mt-0 pt-0">
-                                            <EpisodePicker season={Some(s)} />
+                                            <EpisodePicker season={Some(s)} onselect={&on_select} />
                                         </div>
                                     </div>
                                 </div>
@@ -660,7 +961,32 @@ impl Component for ScheduleShow {
                             acc  // Fold in season
                         }); // EpisodePicker
                         html! {
+                            <>
+                            <div class="box episode-rules">
+                                <h1 class="is-size-5">{"Selection Rules"}</h1>
+                                <div class="columns is-multiline is-variable">
+                                    <div class="column is-half">
+                                        <input class="is-checkradio is-success" id="rule_only_unwatched" type="checkbox" />
+                                        <label for="rule_only_unwatched">{"Only Unwatched"}</label>
+                                    </div>
+                                    <div class="column is-half">
+                                        <label for="rule_min_runtime">{"Skip Shorter Than (min) "}</label>
+                                        <input class="input is-small" id="rule_min_runtime" type="number" min="0" value="0" />
+                                    </div>
+                                    <div class="column is-half">
+                                        <label for="rule_aired_after">{"Aired After "}</label>
+                                        <input id="rule_aired_after" type="date" />
+                                    </div>
+                                    <div class="column is-half">
+                                        <label for="rule_range_start">{"Range "}</label>
+                                        <input class="input is-small range-input" id="rule_range_start" type="text" placeholder="S01E01" />
+                                        {" - "}
+                                        <input class="input is-small range-input" id="rule_range_end" type="text" placeholder="S04E10" />
+                                    </div>
+                                </div>
+                            </div>
                             {for seasons}
+                            </>
                         }
                     }
                     MediaType::movie => {
@@ -701,16 +1027,9 @@ impl Component for ScheduleShow {
             ScheduleShowState::EpisodeScheduler => {
                 // TODO: Most of these options could have user-defined defaults
                 title = format!("{} Episodes to Distribute", self.episodes_to_schedule.len());
-                let days_of_week = [
-                    "Monday",
-                    "Tuesday",
-                    "Wednesday",
-                    "Thursday",
-                    "Friday",
-                    "Saturday",
-                    "Sunday",
-                ];
-                let range_start = Local::now();
+                // Shows still in production default to waiting on air dates.
+                let show_airing = self.show.as_ref().is_some_and(|s| s.in_production);
+                let range_start = Utc::now().with_timezone(&UserTimeZone::get());
                 let range_end = range_start.add(Duration::weeks(4));
                 let start_date_string = range_start.format(date_format).to_string();
                 let start_time_string = range_start.format(time_format).to_string();
@@ -747,7 +1066,7 @@ impl Component for ScheduleShow {
                             <div class="field-body">
                                 <div class="field">
                                     <div class="control">
-                                        <input id="pickerTimeStart" type="time" value={start_time_string} />
+                                        <input id="pickerTimeStart" type="time" value={start_time_string.clone()} />
                                     </div>
                                 </div>
                             </div>
@@ -759,7 +1078,7 @@ impl Component for ScheduleShow {
                             <div class="field-body">
                                 <div class="field">
                                     <div class="control">
-                                        <input id="pickerTimeEnd" type="time" value={end_time_string} />
+                                        <input id="pickerTimeEnd" type="time" value={end_time_string.clone()} />
                                     </div>
                                 </div>
                             </div>
@@ -805,46 +1124,116 @@ impl Component for ScheduleShow {
                         // />
                         <div class="box">
                             <h1 class="is-size-4">{"Scheduling Options"}</h1>
+                            <table class="table is-fullwidth is-narrow day-windows">
+                                <thead>
+                                    <tr>
+                                        <th>{"Day"}</th>
+                                        <th>{"From"}</th>
+                                        <th title="An earlier time than From runs past midnight">{"Until"}</th>
+                                        <th>{"Episodes"}</th>
+                                    </tr>
+                                </thead>
+                                <tbody>
+                                {
+                                    DAYS_OF_WEEK.into_iter().enumerate().map(|(idx, day)| {
+                                        html!{
+                                            <tr>
+                                                <td>
+                                                    <input class="is-checkradio is-success"
+                                                            id={format!("checkbox_dow_{day}")}
+                                                            type="checkbox"
+                                                            checked={matches!(idx, 0..=4)}
+                                                    />
+                                                    <label for={format!("checkbox_dow_{day}")}>
+                                                        {day}
+                                                    </label>
+                                                </td>
+                                                <td>
+                                                    <input id={format!("pickerTimeStart_{day}")} type="time"
+                                                        value={start_time_string.clone()} />
+                                                </td>
+                                                <td>
+                                                    <input id={format!("pickerTimeEnd_{day}")} type="time"
+                                                        value={end_time_string.clone()} />
+                                                </td>
+                                                <td>
+                                                    <div class="select is-small">
+                                                        <select id={format!("episodesPerDay_{day}")}>
+                                                            // 6 Eps a day? Might as well fill...
+                                                            {(1..=6).into_iter().map(|idx|
+                                                                html!{ <option value={idx.to_string()} selected={idx==2}>{idx}</option> }
+                                                            ).collect::<Html>()}
+                                                            <option value="0">{"Fill"}</option>
+                                                        </select>
+                                                    </div>
+                                                </td>
+                                            </tr>
+                                        }
+                                    }).collect::<Html>()
+                                }
+                                </tbody>
+                            </table>
+                        </div>
+                        <div class="box">
+                            <h1 class="is-size-4">{"Breaks and Padding"}</h1>
                             <div class="columns is-variable">
                                 <div class="column">
-                                    <div>
-                                        <p>{"Days of Week"}</p>
-                                        <div class="columns is-multiline is-gapless">
-                                            {
-                                                days_of_week.into_iter().enumerate().map(|(idx, day)| {
-                                                    html!{
-                                                        <div class="column is-half">
-                                                            <div class="field">
-                                                                <input class="is-checkradio is-success"
-                                                                        id={format!("checkbox_dow_{day}")}
-                                                                        type="checkbox"
-                                                                        checked={matches!(idx, 0..=4)}
-                                                                />
-                                                                <label for={format!("checkbox_dow_{day}")}>
-                                                                    {day}
-                                                                </label>
-                                                            </div>
-                                                        </div>
-                                                    }
-                                                }).collect::<Html>()
-                                            }
-                                        </div>
+                                    <p>{"Minutes Between Episodes"}</p>
+                                    <input class="input is-small" id="gapMinutes" type="number" min="0" value="0" />
+                                </div>
+                                <div class="column">
+                                    <p>{"Round Start Times"}</p>
+                                    <div class="select is-small">
+                                        <select id="roundStartTimes">
+                                            <option value="0" selected=true>{"Off"}</option>
+                                            {[5, 15, 30].into_iter().map(|mins|
+                                                html!{ <option value={mins.to_string()}>{format!("{mins} min")}</option> }
+                                            ).collect::<Html>()}
+                                        </select>
                                     </div>
                                 </div>
                                 <div class="column">
-                                    <div class="mb-1">
-                                        <p>{"Episodes per Day"}</p>
-                                        <div>
-                                            <div class="select">
-                                                <select id="episodesPerDay">
-                                                    // 6 Eps a day? Might as well fill...
-                                                    {(1..=6).into_iter().map(|idx|
-                                                        html!{ <option value={idx.to_string()} selected={idx==2}>{idx}</option> }
-                                                    ).collect::<Html>()}
-                                                    <option value="0">{"Fill"}</option>
-                                                </select>
-                                            </div>
-                                        </div>
+                                    <p>{"Long Break Every"}</p>
+                                    <div class="select is-small">
+                                        <select id="longBreakEvery">
+                                            <option value="0" selected=true>{"Never"}</option>
+                                            {(2..=6).into_iter().map(|idx|
+                                                html!{ <option value={idx.to_string()}>{format!("{idx} Episodes")}</option> }
+                                            ).collect::<Html>()}
+                                        </select>
+                                    </div>
+                                </div>
+                                <div class="column">
+                                    <p>{"Long Break Minutes"}</p>
+                                    <input class="input is-small" id="longBreakMinutes" type="number" min="0" value="30" />
+                                </div>
+                            </div>
+                        </div>
+                        <div class="box">
+                            <h1 class="is-size-4">{"Air Dates"}</h1>
+                            <div class="columns is-variable">
+                                <div class="column">
+                                    <p>{"Mode"}</p>
+                                    <div class="select is-small">
+                                        <select id="airDateMode">
+                                            <option value="ignore" selected={!show_airing}>{"Ignore Air Dates"}</option>
+                                            <option value="air_date" selected={show_airing}>{"Not Before Air Date"}</option>
+                                            <option value="follow_along">{"Follow Along Weekly"}</option>
+                                        </select>
+                                    </div>
+                                </div>
+                                <div class="column">
+                                    <p>{"Days After Airing"}</p>
+                                    <input class="input is-small" id="airDateDelay" type="number" min="0" value="0" />
+                                </div>
+                                <div class="column">
+                                    <p>{"Follow Along On"}</p>
+                                    <div class="select is-small">
+                                        <select id="followAlongDay">
+                                            {DAYS_OF_WEEK.into_iter().enumerate().map(|(idx, day)|
+                                                html!{ <option value={idx.to_string()}>{day}</option> }
+                                            ).collect::<Html>()}
+                                        </select>
                                     </div>
                                 </div>
                             </div>
@@ -855,7 +1244,7 @@ impl Component for ScheduleShow {
             }
             ScheduleShowState::MovieScheduler => {
                 title = self.show.clone().unwrap().title.unwrap();
-                let range_start = Local::now();
+                let range_start = Utc::now().with_timezone(&UserTimeZone::get());
                 let start_date_string = range_start.format(date_format).to_string();
                 let start_time_string = range_start.format(time_format).to_string();
 
@@ -916,11 +1305,18 @@ impl Component for ScheduleShow {
                         // TODO: Clean up, currently clunky.
                         if self.schedule_show_state == ScheduleShowState::ShowPicker {
                             <button class="button" onclick={&on_distribute}>{"Distribute"}</button>
+                            <span class="ml-2 is-size-7">
+                                {format!("{} episodes selected | {} min", selected_count, selected_runtime)}
+                            </span>
                             // <button class="button control" onclick={on_cancel}>{"Cancel"}</button>
                         } if self.schedule_show_state == ScheduleShowState::EpisodeScheduler {
                             <button class="button" onclick={&on_schedule}>{"Schedule"}</button>
+                            if !redistributing {
+                                <span class="ml-2">{calendar_select(&self.calendars, "scheduleCalendar", calendar)}</span>
+                            }
                         } if self.schedule_show_state == ScheduleShowState::MovieScheduler {
                             <button class="button" onclick={&on_schedule_movie}>{"Apply"}</button>
+                            <span class="ml-2">{calendar_select(&self.calendars, "scheduleCalendar", calendar)}</span>
                         }
                     </footer>
                 </div>
@@ -943,3 +1339,151 @@ impl Component for ScheduleShow {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn episodes(count: usize, run_time: usize) -> Vec<Episode> {
+        (1..=count)
+            .map(|n| Episode {
+                air_date: String::from("2023-01-01"),
+                episode_number: n,
+                name: format!("Episode {}", n),
+                id: n,
+                season_number: 1,
+                still_path: None,
+                episode_run_time: run_time,
+                show_name: String::from("Show"),
+                show_id: 1,
+            })
+            .collect()
+    }
+
+    fn time(hour: u32, min: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, min, 0).unwrap()
+    }
+
+    // 2023-01-02 is a Monday.
+    fn bounds(end_day: u32) -> SchedulingBoundaries {
+        SchedulingBoundaries {
+            start_date: NaiveDate::from_ymd_opt(2023, 1, 2).unwrap(),
+            start_time: NaiveTime::MIN,
+            end_date: NaiveDate::from_ymd_opt(2023, 1, end_day).unwrap(),
+            end_time: time(23, 59),
+            time_zone: Tz::UTC,
+        }
+    }
+
+    fn options(days: &[u32], start: NaiveTime, end: NaiveTime, eps_per_day: usize) -> SchedulingOptions {
+        SchedulingOptions {
+            days_of_week: days
+                .iter()
+                .map(|&d| {
+                    (
+                        d,
+                        DayWindow {
+                            enabled: true,
+                            start_time: start,
+                            end_time: end,
+                            eps_per_day,
+                        },
+                    )
+                })
+                .collect(),
+            use_end_date: false,
+            gap_minutes: 0,
+            round_to_minutes: 0,
+            long_break_every: 0,
+            long_break_minutes: 0,
+            air_date_mode: AirDateMode::Ignore,
+        }
+    }
+
+    fn dates(events: &[ScheduledEvent]) -> Vec<String> {
+        events
+            .iter()
+            .map(|se| se.scheduled_date.format("%a %d %H:%M").to_string())
+            .collect()
+    }
+
+    #[test]
+    fn fills_each_window_in_order() {
+        let options = options(&[0, 2], time(20, 0), time(21, 0), 0);
+        let scheduled = distribute_episodes(&episodes(4, 30), &bounds(31), &options).unwrap();
+
+        assert_eq!(
+            dates(&scheduled),
+            ["Mon 02 20:00", "Mon 02 20:30", "Wed 04 20:00", "Wed 04 20:30"]
+        );
+    }
+
+    #[test]
+    fn limits_episodes_per_day() {
+        let options = options(&[0, 1, 2, 3, 4, 5, 6], time(20, 0), time(23, 0), 1);
+        let scheduled = distribute_episodes(&episodes(3, 30), &bounds(31), &options).unwrap();
+
+        assert_eq!(dates(&scheduled), ["Mon 02 20:00", "Tue 03 20:00", "Wed 04 20:00"]);
+    }
+
+    #[test]
+    fn gaps_breaks_and_rounding() {
+        let mut options = options(&[0], time(20, 0), time(23, 59), 0);
+        options.gap_minutes = 5;
+        options.round_to_minutes = 15;
+        options.long_break_every = 2;
+        options.long_break_minutes = 30;
+        let scheduled = distribute_episodes(&episodes(3, 22), &bounds(31), &options).unwrap();
+
+        assert_eq!(dates(&scheduled), ["Mon 02 20:00", "Mon 02 20:30", "Mon 02 21:30"]);
+    }
+
+    #[test]
+    fn window_past_midnight_ends_the_next_day() {
+        let options = options(&[0], time(23, 0), time(1, 0), 0);
+        let scheduled = distribute_episodes(&episodes(4, 45), &bounds(31), &options).unwrap();
+
+        assert_eq!(
+            dates(&scheduled),
+            ["Mon 02 23:00", "Mon 02 23:45", "Mon 09 23:00", "Mon 09 23:45"]
+        );
+    }
+
+    #[test]
+    fn holds_episodes_until_they_air() {
+        let mut eps = episodes(2, 30);
+        eps[1].air_date = String::from("2023-01-05");
+        let mut options = options(&[0, 1, 2, 3, 4, 5, 6], time(20, 0), time(23, 0), 0);
+        options.air_date_mode = AirDateMode::NotBeforeAirDate { delay_days: 1 };
+        let scheduled = distribute_episodes(&eps, &bounds(31), &options).unwrap();
+
+        assert_eq!(dates(&scheduled), ["Mon 02 20:00", "Fri 06 20:00"]);
+    }
+
+    #[test]
+    fn errors_past_the_end_date() {
+        let mut options = options(&[0], time(20, 0), time(21, 0), 0);
+        options.use_end_date = true;
+
+        assert!(distribute_episodes(&episodes(2, 30), &bounds(8), &options).is_ok());
+        assert!(distribute_episodes(&episodes(3, 30), &bounds(8), &options).is_err());
+        options.use_end_date = false;
+        assert!(distribute_episodes(&episodes(3, 30), &bounds(8), &options).is_ok());
+    }
+
+    #[test]
+    fn errors_when_an_episode_fits_no_window() {
+        let options = options(&[0], time(20, 0), time(21, 0), 0);
+
+        assert!(distribute_episodes(&episodes(1, 60), &bounds(31), &options).is_ok());
+        assert!(distribute_episodes(&episodes(1, 61), &bounds(31), &options).is_err());
+    }
+
+    #[test]
+    fn errors_without_any_days() {
+        let mut options = options(&[0], time(20, 0), time(21, 0), 0);
+        options.days_of_week.get_mut(&0).unwrap().enabled = false;
+
+        assert!(distribute_episodes(&episodes(1, 30), &bounds(31), &options).is_err());
+    }
+}
@@ -405,7 +405,13 @@ impl TMDB {
     pub async fn get_seasons_episodes(&self, id: &String) -> Option<Vec<TMDBSeasonObj>> {
         match self.get_tv(id).await {
             Ok(show) => {
-                let seasons: Vec<_> = (1..=show.number_of_seasons)
+                // Use the show's own season list so specials (season 0) come along when they exist.
+                let mut season_numbers: Vec<usize> =
+                    show.seasons.iter().map(|s| s.season_number).collect();
+                if season_numbers.is_empty() {
+                    season_numbers = (1..=show.number_of_seasons).collect();
+                }
+                let seasons: Vec<_> = season_numbers
                     .into_iter()
                     .map(|sn| async move {
                         let season = async { self.get_tv_season(id, sn).await };
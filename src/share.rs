@@ -0,0 +1,181 @@
+use std::error::Error;
+
+use chrono::{Datelike, NaiveDate};
+use itertools::Itertools;
+use uuid::Uuid;
+use weblog::console_log;
+use yew::prelude::*;
+use yew_router::prelude::*;
+
+use crate::calendars::Calendars;
+use crate::event_calendar::{calendar_route, get_calendar_cells, CalendarView};
+use crate::event_manager::EventManager;
+use crate::events::ScheduledEvent;
+use crate::time_zone::UserTimeZone;
+use crate::Route;
+
+// Events travel as RON, deflated, then URL safe base64 so they fit in a /shared/{payload} link.
+// Watch logs, watch parties (guests' emails, where you're meeting) and tags are personal so they're left behind.
+pub fn encode_events(events: &[ScheduledEvent]) -> Result<String, Box<dyn Error>> {
+    let events = events
+        .iter()
+        .cloned()
+        .map(|se| ScheduledEvent { watch_log: vec![], party: None, tags: vec![], ..se })
+        .collect::<Vec<ScheduledEvent>>();
+    let ron = ron::to_string(&events)?;
+    let deflated = miniz_oxide::deflate::compress_to_vec(ron.as_bytes(), 9);
+
+    Ok(base64::encode_config(deflated, base64::URL_SAFE_NO_PAD))
+}
+
+// Far more than any real schedule, small enough that a hostile link can't eat the tab's memory.
+const MAX_DECODED_BYTES: usize = 4 * 1024 * 1024;
+
+pub fn decode_events(payload: &str) -> Result<Vec<ScheduledEvent>, Box<dyn Error>> {
+    let deflated = base64::decode_config(payload, base64::URL_SAFE_NO_PAD)?;
+    let ron = miniz_oxide::inflate::decompress_to_vec_with_limit(&deflated, MAX_DECODED_BYTES)
+        .map_err(|e| format!("Bad shared payload: {e:?}"))?;
+
+    Ok(ron::de::from_bytes(&ron)?)
+}
+
+// Full link for a set of events, on whatever host Bynger is being served from.
+pub fn share_link(events: &[ScheduledEvent]) -> Result<String, Box<dyn Error>> {
+    let origin = gloo_utils::window().location().origin().map_err(|_| "No origin")?;
+    let path = Route::Shared { payload: encode_events(events)? }.to_path();
+
+    Ok(format!("{origin}{path}"))
+}
+
+pub(crate) fn short_title(se: &ScheduledEvent) -> String {
+    match (&se.episode, &se.movie) {
+        (Some(ep), _) => format!("{} S{:02}E{:02}", ep.show_name, ep.season_number, ep.episode_number),
+        (_, Some(mv)) => mv.show_name.clone(),
+        _ => String::from("Unknown"),
+    }
+}
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct SharedCalendarProps {
+    pub payload: String,
+}
+
+// A read only month by month calendar of someone else's events, with the option to take them.
+#[function_component(SharedCalendar)]
+pub fn shared_calendar(props: &SharedCalendarProps) -> Html {
+    let tz = UserTimeZone::get();
+    let navigator = use_navigator();
+    let events = match decode_events(&props.payload) {
+        Ok(events) => events,
+        Err(e) => {
+            console_log!(format!("BYNGER - Shared Link Failed - {e}"));
+            return html! {
+                <div class="box shared-calendar">
+                    <p>{"This shared link is broken or incomplete."}</p>
+                </div>
+            };
+        }
+    };
+    let local_date = move |se: &ScheduledEvent| se.scheduled_date.with_timezone(&tz).date_naive();
+
+    let onimport = {
+        let events = events.clone();
+        Callback::from(move |_| {
+            let mut em = EventManager::create();
+            // They land in whichever calendar is being looked at, the sender's calendars mean nothing here.
+            let calendar = Calendars::load().active;
+            // Fresh ids so they can't collide with (or overwrite through sync) anyone else's events.
+            // Importing the same link twice still shouldn't double up, so match on what and when instead.
+            let same_event = |a: &ScheduledEvent, b: &ScheduledEvent| {
+                a.scheduled_date == b.scheduled_date
+                    && a.episode.as_ref().map(|ep| ep.id) == b.episode.as_ref().map(|ep| ep.id)
+                    && a.movie.as_ref().map(|mv| mv.movie_id) == b.movie.as_ref().map(|mv| mv.movie_id)
+            };
+            let new_events = events
+                .iter()
+                .filter(|se| !em.events.iter().any(|existing| same_event(existing, se)))
+                .map(|se| ScheduledEvent { uuid: Uuid::new_v4(), calendar, ..se.clone() })
+                .collect::<Vec<ScheduledEvent>>();
+            let first = new_events.first().map(local_date);
+            match em.add_events(new_events) {
+                Ok(_) => console_log!("BYNGER - Shared Events Imported"),
+                Err(e) => console_log!(format!("BYNGER - Shared Import Failed - {e}")),
+            }
+            if let (Some(navigator), Some(first)) = (&navigator, first) {
+                navigator.push(&calendar_route(CalendarView::Month, first));
+            }
+        })
+    };
+
+    let months = events
+        .iter()
+        .map(|se| {
+            let d = local_date(se);
+            (d.year(), d.month())
+        })
+        .unique()
+        .sorted()
+        .filter_map(|(year, month)| NaiveDate::from_ymd_opt(year, month, 1))
+        .map(|month| {
+            let weeks = get_calendar_cells(&month)
+                .chunks(7)
+                .map(|week| {
+                    let days = week
+                        .iter()
+                        .map(|&d| match d {
+                            None => html! { <td></td> },
+                            Some(d) => {
+                                let items = events
+                                    .iter()
+                                    .filter(|se| local_date(se) == d)
+                                    .map(|se| {
+                                        let start = se.scheduled_date.with_timezone(&tz);
+                                        html! {
+                                            <p class="shared-event">
+                                                {format!("{} {}", start.format("%R"), short_title(se))}
+                                            </p>
+                                        }
+                                    })
+                                    .collect::<Html>();
+                                html! {
+                                    <td>
+                                        <span class="has-text-weight-semibold">{d.format("%d").to_string()}</span>
+                                        {items}
+                                    </td>
+                                }
+                            }
+                        })
+                        .collect::<Html>();
+                    html! { <tr>{days}</tr> }
+                })
+                .collect::<Html>();
+
+            html! {
+                <>
+                <p class="subtitle mt-3">{month.format("%B %Y").to_string()}</p>
+                <table class="table is-fullwidth is-bordered shared-month">
+                    <thead>
+                        <tr>
+                            <th>{"MON"}</th><th>{"TUE"}</th><th>{"WED"}</th><th>{"THU"}</th>
+                            <th>{"FRI"}</th><th>{"SAT"}</th><th>{"SUN"}</th>
+                        </tr>
+                    </thead>
+                    <tbody>{weeks}</tbody>
+                </table>
+                </>
+            }
+        })
+        .collect::<Html>();
+
+    html! {
+        <div class="box shared-calendar">
+            <nav class="level">
+                <p class="level-left">{format!("Someone shared {} events with you.", events.len())}</p>
+                <p class="level-right">
+                    <button class="button is-success" onclick={onimport}>{"Add To My Calendar"}</button>
+                </p>
+            </nav>
+            {months}
+        </div>
+    }
+}
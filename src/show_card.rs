@@ -1,4 +1,5 @@
 use crate::search_client::{MediaType, TMDBMovieObj, TMDBTVObj, TMDB};
+use crate::watchlist::{WatchlistEntry, WatchlistManager};
 use std::fmt::Display;
 use weblog::console_error;
 use yew::prelude::*;
@@ -84,6 +85,7 @@ pub enum ShowCardMsg {
     LoadingError(String),
     ShowFound(Box<Show>),
     ShowClicked,
+    AddToWatchlist,
 }
 impl From<()> for ShowCardMsg {
     fn from(_val: ()) -> Self {
@@ -155,6 +157,13 @@ impl Component for ShowCard {
                 ctx.props().onclick.emit(_out);
                 false
             }
+            ShowCardMsg::AddToWatchlist => {
+                let entry = WatchlistEntry::from(&ctx.props().show);
+                if let Err(e) = WatchlistManager::create().add(entry) {
+                    console_error!(format!("BYNGER - Watchlist Update Failed - {}", e));
+                }
+                true
+            }
         }
     }
 
@@ -168,6 +177,8 @@ impl Component for ShowCard {
                 html! { /* do nothing */ }
             }
             Some(s) => {
+                let listed = WatchlistManager::create().contains(&s.id, &s.media_type);
+                let onwatchlist = ctx.link().callback(|_e: MouseEvent| ShowCardMsg::AddToWatchlist);
                 let title = &s.title.unwrap_or_default();
                 let season_count = ShowCard::value_into_pair("Seasons", &s.number_of_seasons);
                 let episode_count = ShowCard::value_into_pair("Episodes", &s.number_of_episodes);
@@ -196,6 +207,13 @@ impl Component for ShowCard {
                             <div class="card-footer-item pl-1 pr-1 pt-0 pb-0" onclick={onclick}>
                                 <div class="is-clickable pl-0">{"Pick"}</div>
                             </div>
+                            if listed {
+                                <div class="card-footer-item pl-1 pr-1 pt-0 pb-0 has-text-grey">{"Listed"}</div>
+                            } else {
+                                <div class="card-footer-item pl-1 pr-1 pt-0 pb-0" onclick={onwatchlist}>
+                                    <div class="is-clickable pl-0">{"Watchlist"}</div>
+                                </div>
+                            }
                         </footer>
                     </div>
                 }
@@ -0,0 +1,145 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Duration, Utc};
+use chrono_tz::Tz;
+use yew::prelude::*;
+
+use crate::events::ScheduledEvent;
+
+// How far along a show is, worked out from its scheduled events.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShowProgress {
+    pub show_id: usize,
+    pub show_name: String,
+    pub watched: usize,
+    pub total: usize,
+    pub runtime_watched: usize,   // in Minutes
+    pub runtime_remaining: usize, // in Minutes
+    // When the last unwatched episode ends, if the schedule is followed.
+    pub projected_finish: Option<DateTime<Utc>>,
+    // Earliest unwatched episode in season/episode order.
+    pub up_next: Option<ScheduledEvent>,
+}
+
+impl ShowProgress {
+    // One entry per show with episodes scheduled, ordered by show name.
+    pub fn from_events(events: &[ScheduledEvent]) -> Vec<ShowProgress> {
+        let mut shows: BTreeMap<usize, Vec<&ScheduledEvent>> = BTreeMap::new();
+        for se in events {
+            if let Some(ep) = &se.episode {
+                shows.entry(ep.show_id).or_default().push(se);
+            }
+        }
+
+        let mut progress = shows
+            .into_iter()
+            .map(|(show_id, mut show_events)| {
+                show_events.sort_by_key(|se| {
+                    let ep = se.episode.as_ref().unwrap();
+                    (ep.season_number, ep.episode_number, se.scheduled_date)
+                });
+                let (watched, unwatched): (Vec<&ScheduledEvent>, Vec<&ScheduledEvent>) =
+                    show_events.iter().partition(|se| se.watched());
+                let runtime = |list: &[&ScheduledEvent]| -> usize {
+                    list.iter()
+                        .map(|se| se.episode.as_ref().unwrap().episode_run_time)
+                        .sum()
+                };
+                let projected_finish = unwatched
+                    .iter()
+                    .map(|se| {
+                        let minutes = se.episode.as_ref().unwrap().episode_run_time as i64;
+                        se.scheduled_date + Duration::minutes(minutes)
+                    })
+                    .max();
+
+                ShowProgress {
+                    show_id,
+                    show_name: show_events[0].episode.as_ref().unwrap().show_name.clone(),
+                    watched: watched.len(),
+                    total: show_events.len(),
+                    runtime_watched: runtime(&watched),
+                    runtime_remaining: runtime(&unwatched),
+                    projected_finish,
+                    up_next: unwatched.first().map(|&se| se.clone()),
+                }
+            })
+            .collect::<Vec<ShowProgress>>();
+        progress.sort_by(|a, b| a.show_name.cmp(&b.show_name));
+
+        progress
+    }
+
+    // Started but not finished.
+    pub fn in_progress(&self) -> bool {
+        self.watched > 0 && self.watched < self.total
+    }
+}
+
+fn hours_minutes(minutes: usize) -> String {
+    match (minutes / 60, minutes % 60) {
+        (0, m) => format!("{m}m"),
+        (h, 0) => format!("{h}h"),
+        (h, m) => format!("{h}h {m}m"),
+    }
+}
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct UpNextProps {
+    pub progress: Vec<ShowProgress>,
+    pub time_zone: Tz,
+    pub onclick: Callback<Option<ScheduledEvent>>,
+}
+
+#[function_component(UpNext)]
+pub fn up_next(props: &UpNextProps) -> Html {
+    let tz = props.time_zone;
+    let shows = props
+        .progress
+        .iter()
+        .filter(|sp| sp.in_progress())
+        .filter_map(|sp| {
+            let se = sp.up_next.clone()?;
+            let ep = se.episode.clone()?;
+            let start = se.scheduled_date.with_timezone(&tz);
+            let finish = sp
+                .projected_finish
+                .map(|f| format!(", done {}", f.with_timezone(&tz).format("%b %d")))
+                .unwrap_or_default();
+            let oce = props.onclick.clone();
+            let onclick = Callback::from(move |_| oce.emit(Some(se.clone())));
+
+            Some(html! {
+                <a class="panel-block up-next-item" {onclick}>
+                    <div>
+                        <p>
+                            <strong>{&sp.show_name}</strong>
+                            {format!(" S{:02}E{:02} {}", ep.season_number, ep.episode_number, ep.name)}
+                        </p>
+                        <p class="is-size-7">
+                            {format!("{} | {}/{} watched ({}), {} left{finish}",
+                                start.format("%a %b %d %R"),
+                                sp.watched,
+                                sp.total,
+                                hours_minutes(sp.runtime_watched),
+                                hours_minutes(sp.runtime_remaining))}
+                        </p>
+                        <progress class="progress is-info is-small"
+                            value={sp.watched.to_string()} max={sp.total.to_string()}></progress>
+                    </div>
+                </a>
+            })
+        })
+        .collect::<Vec<Html>>();
+
+    if shows.is_empty() {
+        return html! {};
+    }
+
+    html! {
+        <nav class="panel up-next mt-3">
+            <p class="panel-heading">{"Up Next"}</p>
+            {shows}
+        </nav>
+    }
+}
@@ -1,14 +1,29 @@
 use std::fmt::{Display, Formatter};
+use chrono_tz::TZ_VARIANTS;
 use gloo::storage::{LocalStorage, Storage};
 use wasm_bindgen::JsCast;
-use web_sys::{EventTarget, HtmlInputElement};
+use web_sys::{EventTarget, HtmlInputElement, HtmlSelectElement};
 use weblog::{console_error, console_info};
 use yew::prelude::*;
 
+use crate::catch_up::CatchUpStrategy;
+use crate::time_zone::UserTimeZone;
+
 #[derive(Clone, PartialEq, Eq)]
 pub enum ByngerStore {
     TmdbApiKey = 0,
     ScheduledEvents = 1,
+    TimeZone = 2,
+    TrackedShows = 3,
+    EventHistory = 4,
+    CatchUpPolicy = 5,
+    Watchlist = 6,
+    SyncSettings = 7,
+    SyncState = 8,
+    Calendars = 9,
+    Tags = 10,
+    StorageVersion = 11,
+    EditTimes = 12,
 }
 
 impl Display for ByngerStore {
@@ -17,6 +32,17 @@ impl Display for ByngerStore {
         let name = match self {
             ByngerStore::TmdbApiKey => "TMDB_API_KEY",
             ByngerStore::ScheduledEvents => "SCHEDULED_EVENTS",
+            ByngerStore::TimeZone => "TIME_ZONE",
+            ByngerStore::TrackedShows => "TRACKED_SHOWS",
+            ByngerStore::EventHistory => "EVENT_HISTORY",
+            ByngerStore::CatchUpPolicy => "CATCH_UP_POLICY",
+            ByngerStore::Watchlist => "WATCHLIST",
+            ByngerStore::SyncSettings => "SYNC_SETTINGS",
+            ByngerStore::SyncState => "SYNC_STATE",
+            ByngerStore::Calendars => "CALENDARS",
+            ByngerStore::Tags => "TAGS",
+            ByngerStore::StorageVersion => "STORAGE_VERSION",
+            ByngerStore::EditTimes => "EDIT_TIMES",
         };
         write!(f, "{prefix}_{name}")
     }
@@ -25,11 +51,15 @@ impl Display for ByngerStore {
 #[derive(Clone, PartialEq, Eq)]
 pub struct SiteConfig {
     tmdb_api_key: Option<String>,
+    time_zone: Option<String>, // None = Use the browser's time zone.
+    catch_up_policy: Option<CatchUpStrategy>, // None = Leave missed events where they are.
     schedule_entries: Option<Vec<String>>,
 }
 
 pub enum SiteConfigMsg {
     Update(String),
+    UpdateTimeZone(String),
+    UpdateCatchUpPolicy(String),
     Save,
 }
 
@@ -40,6 +70,7 @@ impl Component for SiteConfig {
     fn create(_ctx: &Context<Self>) -> Self {
         let tmdb_api_key =
             LocalStorage::get(ByngerStore::TmdbApiKey.to_string()).unwrap_or_default();
+        let time_zone = LocalStorage::get(ByngerStore::TimeZone.to_string()).unwrap_or_default();
         let schedule_entries = Some(
             LocalStorage::get(ByngerStore::ScheduledEvents.to_string())
                 .unwrap_or_else(|_| Vec::new()),
@@ -47,6 +78,8 @@ impl Component for SiteConfig {
 
         Self {
             tmdb_api_key,
+            time_zone,
+            catch_up_policy: CatchUpStrategy::policy(),
             schedule_entries,
         }
     }
@@ -57,6 +90,18 @@ impl Component for SiteConfig {
                 self.tmdb_api_key = Some(key);
                 false
             }
+            SiteConfigMsg::UpdateTimeZone(tz) => {
+                self.time_zone = (!tz.is_empty()).then_some(tz);
+                false
+            }
+            SiteConfigMsg::UpdateCatchUpPolicy(policy) => {
+                self.catch_up_policy = match policy.as_str() {
+                    "shift" => Some(CatchUpStrategy::ShiftForward),
+                    "reslot" => Some(CatchUpStrategy::Reslot),
+                    _ => None,
+                };
+                false
+            }
             SiteConfigMsg::Save => {
                 let stored = LocalStorage::set(
                     ByngerStore::TmdbApiKey.to_string(),
@@ -70,6 +115,31 @@ impl Component for SiteConfig {
                         console_error!("Bynger || Error storing API Key");
                     }
                 }
+                let stored = match &self.time_zone {
+                    None => {
+                        LocalStorage::delete(ByngerStore::TimeZone.to_string());
+                        Ok(())
+                    }
+                    Some(tz) => LocalStorage::set(ByngerStore::TimeZone.to_string(), tz),
+                };
+                match stored {
+                    Ok(_) => {
+                        console_info!("Bynger || Time Zone Stored");
+                    }
+                    Err(_) => {
+                        console_error!("Bynger || Error storing Time Zone");
+                    }
+                }
+                let stored = match &self.catch_up_policy {
+                    None => {
+                        LocalStorage::delete(ByngerStore::CatchUpPolicy.to_string());
+                        Ok(())
+                    }
+                    Some(policy) => LocalStorage::set(ByngerStore::CatchUpPolicy.to_string(), policy),
+                };
+                if stored.is_err() {
+                    console_error!("Bynger || Error storing Catch Up Policy");
+                }
                 true
             }
         }
@@ -83,6 +153,22 @@ impl Component for SiteConfig {
             let input = target.and_then(|t| t.dyn_into::<HtmlInputElement>().ok());
             input.map(|input| SiteConfigMsg::Update(input.value()))
         });
+        let selected_tz = self.time_zone.clone().unwrap_or_default();
+        let browser_tz = UserTimeZone::browser()
+            .map(|tz| tz.name().to_string())
+            .unwrap_or_else(|| "UTC".to_string());
+        let onchange_tz = ctx.link().batch_callback(|e: Event| {
+            let target: Option<EventTarget> = e.target();
+            let select = target.and_then(|t| t.dyn_into::<HtmlSelectElement>().ok());
+            select.map(|select| SiteConfigMsg::UpdateTimeZone(select.value()))
+        });
+
+        let onchange_catch_up = ctx.link().batch_callback(|e: Event| {
+            let target: Option<EventTarget> = e.target();
+            let select = target.and_then(|t| t.dyn_into::<HtmlSelectElement>().ok());
+            select.map(|select| SiteConfigMsg::UpdateCatchUpPolicy(select.value()))
+        });
+        let policy = self.catch_up_policy;
 
         html! {
             <div class="box">
@@ -93,6 +179,37 @@ impl Component for SiteConfig {
                         id="tmdb_api_key" {onchange} />
                   </div>
                 </div>
+                <div class="field">
+                  <label class="label">{"Time Zone"}</label>
+                  <div class="control">
+                    <div class="select">
+                      <select id="time_zone" onchange={onchange_tz}>
+                        <option value="" selected={selected_tz.is_empty()}>
+                            {format!("Browser Default ({browser_tz})")}
+                        </option>
+                        {TZ_VARIANTS.iter().map(|tz| html!{
+                            <option value={tz.name()} selected={tz.name() == selected_tz}>{tz.name()}</option>
+                        }).collect::<Html>()}
+                      </select>
+                    </div>
+                  </div>
+                </div>
+                <div class="field">
+                  <label class="label">{"Missed Events On Load"}</label>
+                  <div class="control">
+                    <div class="select">
+                      <select id="catch_up_policy" onchange={onchange_catch_up}>
+                        <option value="" selected={policy.is_none()}>{"Leave Them"}</option>
+                        <option value="shift" selected={policy == Some(CatchUpStrategy::ShiftForward)}>
+                            {"Push Shows Forward"}
+                        </option>
+                        <option value="reslot" selected={policy == Some(CatchUpStrategy::Reslot)}>
+                            {"Reslot Into Next Free Slots"}
+                        </option>
+                      </select>
+                    </div>
+                  </div>
+                </div>
                 <div class="control">
                     <button class="button is-primary" {onclick}>{"Save"}</button>
                 </div>
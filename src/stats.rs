@@ -0,0 +1,252 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Timelike, Utc};
+use chrono_tz::Tz;
+use yew::prelude::*;
+
+use crate::event_calendar::export_file;
+use crate::event_manager::EventManager;
+use crate::events::ScheduledEvent;
+use crate::show_progress::ShowProgress;
+use crate::time_zone::UserTimeZone;
+
+// How many of the most recent weeks/months get charted, the CSV has all of them.
+const CHARTED_PERIODS: usize = 12;
+const WEEKDAYS: [&str; 7] = ["MON", "TUE", "WED", "THU", "FRI", "SAT", "SUN"];
+
+// Minutes scheduled and watched in some bucket of time (a week, a weekday, an hour...).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Tally {
+    pub label: String,
+    pub scheduled: i64, // in Minutes
+    pub watched: i64,   // in Minutes
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ViewingStats {
+    pub weeks: Vec<Tally>,
+    pub months: Vec<Tally>,
+    pub weekdays: Vec<Tally>,
+    pub hours: Vec<Tally>,
+    pub shows: Vec<ShowProgress>,
+    pub current_streak: usize, // in Days
+    pub longest_streak: usize,
+}
+
+fn tally_by<K: Ord>(
+    events: &[ScheduledEvent],
+    tz: &Tz,
+    key: impl Fn(DateTime<Tz>) -> K,
+) -> BTreeMap<K, (i64, i64)> {
+    let mut tallies: BTreeMap<K, (i64, i64)> = BTreeMap::new();
+    for se in events {
+        tallies.entry(key(se.scheduled_date.with_timezone(tz))).or_default().0 += se.runtime();
+        // Rewatches count every time.
+        for wr in &se.watch_log {
+            tallies.entry(key(wr.watched_at.with_timezone(tz))).or_default().1 += se.runtime();
+        }
+    }
+
+    tallies
+}
+
+// Consecutive days with something watched, as (current, longest).
+// Today not having anything yet doesn't break the current streak.
+fn streaks(days: &BTreeSet<NaiveDate>, today: NaiveDate) -> (usize, usize) {
+    let mut longest = 0;
+    let mut run = 0;
+    let mut previous: Option<NaiveDate> = None;
+    for &day in days {
+        run = match previous {
+            Some(p) if p + Duration::days(1) == day => run + 1,
+            _ => 1,
+        };
+        longest = longest.max(run);
+        previous = Some(day);
+    }
+
+    let mut day = if days.contains(&today) { today } else { today - Duration::days(1) };
+    let mut current = 0;
+    while days.contains(&day) {
+        current += 1;
+        day -= Duration::days(1);
+    }
+
+    (current, longest)
+}
+
+impl ViewingStats {
+    pub fn from_events(events: &[ScheduledEvent], tz: &Tz, now: DateTime<Utc>) -> Self {
+        let tallies = |map: BTreeMap<String, (i64, i64)>| -> Vec<Tally> {
+            map.into_iter()
+                .map(|(label, (scheduled, watched))| Tally { label, scheduled, watched })
+                .collect()
+        };
+        let weeks = tally_by(events, tz, |dt| {
+            let week = dt.iso_week();
+            format!("{}-W{:02}", week.year(), week.week())
+        });
+        let months = tally_by(events, tz, |dt| dt.format("%Y-%m").to_string());
+        let weekdays = tally_by(events, tz, |dt| dt.weekday().num_days_from_monday());
+        let hours = tally_by(events, tz, |dt| dt.hour());
+
+        let weekdays = (0..7)
+            .map(|wd| {
+                let (scheduled, watched) = weekdays.get(&wd).copied().unwrap_or_default();
+                Tally { label: WEEKDAYS[wd as usize].to_string(), scheduled, watched }
+            })
+            .collect();
+        let hours = (0..24)
+            .map(|h| {
+                let (scheduled, watched) = hours.get(&h).copied().unwrap_or_default();
+                Tally { label: format!("{h:02}:00"), scheduled, watched }
+            })
+            .collect();
+
+        let watch_days = events
+            .iter()
+            .flat_map(|se| se.watch_log.iter())
+            .map(|wr| wr.watched_at.with_timezone(tz).date_naive())
+            .collect::<BTreeSet<NaiveDate>>();
+        let (current_streak, longest_streak) = streaks(&watch_days, now.with_timezone(tz).date_naive());
+
+        ViewingStats {
+            weeks: tallies(weeks),
+            months: tallies(months),
+            weekdays,
+            hours,
+            shows: ShowProgress::from_events(events),
+            current_streak,
+            longest_streak,
+        }
+    }
+
+    pub fn as_csv(&self) -> String {
+        let hours = |minutes: i64| format!("{:.2}", minutes as f64 / 60.0);
+        let mut csv = String::from("Section,Key,Scheduled,Watched\n");
+        let sections = [
+            ("Week (hours)", &self.weeks),
+            ("Month (hours)", &self.months),
+            ("Weekday (hours)", &self.weekdays),
+            ("Hour Of Day (hours)", &self.hours),
+        ];
+        for (section, tallies) in sections {
+            for t in tallies {
+                csv.push_str(&format!("\"{section}\",\"{}\",{},{}\n", t.label, hours(t.scheduled), hours(t.watched)));
+            }
+        }
+        for sp in &self.shows {
+            csv.push_str(&format!("\"Show (episodes)\",\"{}\",{},{}\n", sp.show_name.replace('"', "\"\""), sp.total, sp.watched));
+        }
+        csv.push_str(&format!("\"Streak (days)\",\"Current\",,{}\n", self.current_streak));
+        csv.push_str(&format!("\"Streak (days)\",\"Longest\",,{}\n", self.longest_streak));
+
+        csv
+    }
+}
+
+// Horizontal bars, scheduled behind watched, scaled to the biggest bucket.
+fn bar_chart(tallies: &[Tally]) -> Html {
+    let max = tallies.iter().map(|t| t.scheduled.max(t.watched)).max().unwrap_or(0).max(1);
+    let pct = |minutes: i64| format!("width: {:.1}%;", minutes as f64 * 100.0 / max as f64);
+
+    tallies
+        .iter()
+        .map(|t| {
+            let title = format!(
+                "{:.1}h scheduled, {:.1}h watched",
+                t.scheduled as f64 / 60.0,
+                t.watched as f64 / 60.0
+            );
+            html! {
+                <div class="stats-row" {title}>
+                    <span class="stats-label">{&t.label}</span>
+                    <span class="stats-bars">
+                        <span class="stats-bar is-scheduled" style={pct(t.scheduled)}></span>
+                        <span class="stats-bar is-watched" style={pct(t.watched)}></span>
+                    </span>
+                </div>
+            }
+        })
+        .collect::<Html>()
+}
+
+fn recent(tallies: &[Tally]) -> &[Tally] {
+    &tallies[tallies.len().saturating_sub(CHARTED_PERIODS)..]
+}
+
+#[function_component(Stats)]
+pub fn stats() -> Html {
+    let tz = UserTimeZone::get();
+    let stats = ViewingStats::from_events(&EventManager::create().events, &tz, Utc::now());
+    let onexport = {
+        let csv = stats.as_csv();
+        Callback::from(move |_| {
+            let now = Utc::now().format("%Y%m%d_%H%M%S");
+            export_file(&format!("bynger_stats_{now}.csv"), &csv, "text/csv")
+        })
+    };
+    let shows = stats
+        .shows
+        .iter()
+        .map(|sp| {
+            let pct = sp.watched * 100 / sp.total.max(1);
+            html! {
+                <div class="stats-row">
+                    <span class="stats-label">{&sp.show_name}</span>
+                    <progress class="progress is-info is-small" value={sp.watched.to_string()}
+                        max={sp.total.to_string()}></progress>
+                    <span class="ml-2">{format!("{pct}% ({}/{})", sp.watched, sp.total)}</span>
+                </div>
+            }
+        })
+        .collect::<Html>();
+
+    html! {
+        <div class="box stats">
+            <nav class="level">
+                <div class="level-item has-text-centered">
+                    <div>
+                        <p class="heading">{"Current Streak"}</p>
+                        <p class="title">{format!("{} days", stats.current_streak)}</p>
+                    </div>
+                </div>
+                <div class="level-item has-text-centered">
+                    <div>
+                        <p class="heading">{"Longest Streak"}</p>
+                        <p class="title">{format!("{} days", stats.longest_streak)}</p>
+                    </div>
+                </div>
+                <div class="level-item">
+                    <button class="button" onclick={onexport}>{"export csv"}</button>
+                </div>
+            </nav>
+            <p class="is-size-7 mb-3">
+                <span class="stats-key is-scheduled"></span>{" Scheduled "}
+                <span class="stats-key is-watched"></span>{" Watched"}
+            </p>
+            <div class="columns is-multiline">
+                <div class="column is-half">
+                    <p class="subtitle">{"Weeks"}</p>
+                    {bar_chart(recent(&stats.weeks))}
+                </div>
+                <div class="column is-half">
+                    <p class="subtitle">{"Months"}</p>
+                    {bar_chart(recent(&stats.months))}
+                </div>
+                <div class="column is-half">
+                    <p class="subtitle">{"Weekdays"}</p>
+                    {bar_chart(&stats.weekdays)}
+                </div>
+                <div class="column is-half">
+                    <p class="subtitle">{"Time Of Day"}</p>
+                    {bar_chart(&stats.hours)}
+                </div>
+                <div class="column is-full">
+                    <p class="subtitle">{"Show Completion"}</p>
+                    {shows}
+                </div>
+            </div>
+        </div>
+    }
+}
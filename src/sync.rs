@@ -0,0 +1,605 @@
+use std::collections::BTreeMap;
+
+use bynger_sync::{merge, Conflict, Stamped, SyncRecord, SyncRequest, SyncResponse, DELETED};
+use chrono::{TimeZone, Utc};
+use gloo::storage::errors::StorageError;
+use gloo::storage::{LocalStorage, Storage};
+use reqwasm::http::Request;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use uuid::Uuid;
+use weblog::console_log;
+use yew::prelude::*;
+
+use crate::event_manager::EventManager;
+use crate::events::ScheduledEvent;
+use crate::share::short_title;
+use crate::site_config::ByngerStore;
+use crate::time_zone::UserTimeZone;
+use crate::ui_helpers::UiHelpers;
+
+// Where to sync to, see the readme for running a server.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SyncSettings {
+    pub url: String, // e.g. http://127.0.0.1:8787
+    pub user: String,
+    #[serde(default)]
+    pub token: String, // Empty = the server doesn't need one.
+}
+
+impl SyncSettings {
+    pub fn load() -> Self {
+        LocalStorage::get(ByngerStore::SyncSettings.to_string()).unwrap_or_default()
+    }
+
+    pub fn store(&self) -> Result<(), StorageError> {
+        LocalStorage::set(ByngerStore::SyncSettings.to_string(), self)
+    }
+
+    pub fn configured(&self) -> bool {
+        !self.url.trim().is_empty() && !self.user.trim().is_empty()
+    }
+}
+
+// What the server handed back last time, so we can tell which fields changed locally since.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SyncState {
+    pub last_synced: i64, // Unix time in milliseconds, 0 for never.
+    pub records: Vec<SyncRecord>,
+    pub conflicts: Vec<Conflict>,
+}
+
+impl SyncState {
+    pub fn load() -> Self {
+        LocalStorage::get(ByngerStore::SyncState.to_string()).unwrap_or_default()
+    }
+
+    pub fn store(&self) -> Result<(), StorageError> {
+        LocalStorage::set(ByngerStore::SyncState.to_string(), self)
+    }
+}
+
+// When each field of each event last changed on this device, Unix time in milliseconds.
+// Kept up to date as the EventManager saves, so a sync can tell an old edit from a new one.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EditTimes {
+    pub events: BTreeMap<String, BTreeMap<String, i64>>,
+}
+
+impl EditTimes {
+    pub fn load() -> Self {
+        LocalStorage::get(ByngerStore::EditTimes.to_string()).unwrap_or_default()
+    }
+
+    pub fn store(&self) -> Result<(), StorageError> {
+        LocalStorage::set(ByngerStore::EditTimes.to_string(), self)
+    }
+
+    // Stamps every field that differs between two versions of the calendar, removals included.
+    pub fn record(&mut self, before: &[ScheduledEvent], after: &[ScheduledEvent], now: i64) {
+        for se in after {
+            let prior = before.iter().find(|b| b.uuid == se.uuid);
+            if prior == Some(se) {
+                continue;
+            }
+            let prior_fields = prior.map(event_fields).unwrap_or_default();
+            let stamps = self.events.entry(se.uuid.to_string()).or_default();
+            for (field, value) in event_fields(se) {
+                if prior_fields.get(&field) != Some(&value) {
+                    stamps.insert(field, now);
+                }
+            }
+        }
+        for se in before.iter().filter(|b| !after.iter().any(|a| a.uuid == b.uuid)) {
+            self.events
+                .entry(se.uuid.to_string())
+                .or_default()
+                .insert(DELETED.to_string(), now);
+        }
+    }
+
+    fn modified(&self, uuid: &str, field: &str) -> Option<i64> {
+        self.events.get(uuid)?.get(field).copied()
+    }
+
+    // Once synced, the merged records carry the latest stamp of every field.
+    fn from_records(records: &[SyncRecord]) -> Self {
+        let events = records
+            .iter()
+            .map(|r| {
+                let stamps = r.fields.iter().map(|(field, s)| (field.clone(), s.modified)).collect();
+                (r.uuid.clone(), stamps)
+            })
+            .collect();
+
+        EditTimes { events }
+    }
+}
+
+// Every top level field of the event except its uuid, which is the record's key.
+fn event_fields(se: &ScheduledEvent) -> Map<String, Value> {
+    let mut fields = match serde_json::to_value(se) {
+        Ok(Value::Object(fields)) => fields,
+        _ => Map::new(),
+    };
+    fields.remove("uuid");
+    fields.insert(DELETED.to_string(), Value::Bool(false));
+
+    fields
+}
+
+fn event_from_record(record: &SyncRecord) -> Option<ScheduledEvent> {
+    if record.deleted() {
+        return None;
+    }
+    let mut fields = record
+        .fields
+        .iter()
+        .filter(|(field, _)| field.as_str() != DELETED)
+        .map(|(field, stamped)| (field.clone(), stamped.value.clone()))
+        .collect::<Map<String, Value>>();
+    fields.insert(String::from("uuid"), Value::String(record.uuid.clone()));
+
+    serde_json::from_value(Value::Object(fields)).ok()
+}
+
+// Stamps the local events against the last snapshot. Fields that haven't changed keep their
+// old stamp, anything that has gets the time it was edited. Edits from before edit times were
+// kept fall back to `now`.
+fn local_records(events: &[ScheduledEvent], snapshot: &[SyncRecord], edits: &EditTimes, now: i64) -> Vec<SyncRecord> {
+    let mut records = events
+        .iter()
+        .map(|se| {
+            let uuid = se.uuid.to_string();
+            let prior = snapshot.iter().find(|r| r.uuid == uuid);
+            let fields = event_fields(se)
+                .into_iter()
+                .map(|(field, value)| {
+                    let modified = prior
+                        .and_then(|r| r.fields.get(&field))
+                        .filter(|s| s.value == value)
+                        .map(|s| s.modified)
+                        .or_else(|| edits.modified(&uuid, &field))
+                        .unwrap_or(now);
+                    (field, Stamped { value, modified })
+                })
+                .collect::<BTreeMap<String, Stamped>>();
+            SyncRecord { uuid, fields }
+        })
+        .collect::<Vec<SyncRecord>>();
+
+    // Anything we had last time that's gone now was removed here. Records we couldn't read
+    // never made it in to the calendar, so they're left alone rather than deleted.
+    for record in snapshot {
+        let removed = !record.deleted()
+            && event_from_record(record).is_some()
+            && !events.iter().any(|se| se.uuid.to_string() == record.uuid);
+        if removed {
+            let modified = edits.modified(&record.uuid, DELETED).unwrap_or(now);
+            let mut tombstone = record.clone();
+            tombstone
+                .fields
+                .insert(DELETED.to_string(), Stamped { value: Value::Bool(true), modified });
+            records.push(tombstone);
+        }
+    }
+
+    records
+}
+
+// Anything edited while we waited on the server is newer than what it sent back, so the
+// calendar as it is now gets merged in to the response rather than replaced by it.
+// The server already reported the conflicts, this is only catching up.
+fn catch_up_records(
+    response: &[SyncRecord],
+    events: &[ScheduledEvent],
+    snapshot: &[SyncRecord],
+    edits: &EditTimes,
+    now: i64,
+) -> Vec<SyncRecord> {
+    let mut records = response.to_vec();
+    merge(&mut records, local_records(events, snapshot, edits, now), i64::MAX);
+
+    records
+}
+
+// Pushes local changes, pulls everyone else's, and swaps the merged result in to the calendar.
+pub async fn sync_now() -> Result<SyncState, String> {
+    let settings = SyncSettings::load();
+    if !settings.configured() {
+        return Err(String::from("Sync isn't set up yet."));
+    }
+    let state = SyncState::load();
+    let now = Utc::now().timestamp_millis();
+    let events = EventManager::create().events;
+    let request = SyncRequest {
+        since: state.last_synced,
+        records: local_records(&events, &state.records, &EditTimes::load(), now),
+    };
+    let body = serde_json::to_string(&request).map_err(|e| e.to_string())?;
+    let url = format!("{}/sync/{}", settings.url.trim().trim_end_matches('/'), settings.user.trim());
+
+    let mut post = Request::post(&url)
+        .header("Content-Type", "application/json")
+        .body(body);
+    if !settings.token.is_empty() {
+        post = post.header("Authorization", &format!("Bearer {}", settings.token));
+    }
+    let res = post.send().await.map_err(|e| e.to_string())?;
+    if !res.ok() {
+        return Err(format!("Sync server said {} {}", res.status(), res.status_text()));
+    }
+    let response = res.json::<SyncResponse>().await.map_err(|e| e.to_string())?;
+
+    // The calendar may have changed while we waited, so it's read again here.
+    let mut em = EventManager::create();
+    let records = catch_up_records(
+        &response.records,
+        &em.events,
+        &state.records,
+        &EditTimes::load(),
+        Utc::now().timestamp_millis(),
+    );
+    let merged = records
+        .iter()
+        .filter_map(event_from_record)
+        .collect::<Vec<ScheduledEvent>>();
+    em.set_events(merged, "Sync").map_err(|e| e.to_string())?;
+    // Saving stamps what came from the server as edited just now, put back when it really was.
+    EditTimes::from_records(&records).store().map_err(|e| e.to_string())?;
+
+    // The server's view, so edits made while syncing still count as changes next time.
+    let state = SyncState {
+        last_synced: now,
+        records: response.records,
+        conflicts: response.conflicts,
+    };
+    state.store().map_err(|e| e.to_string())?;
+
+    Ok(state)
+}
+
+// Picks a side for a conflict. Picking the side that lost sets the field locally,
+// the next sync then pushes it out as the newest change.
+pub fn resolve_conflict(index: usize, keep_local: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let mut state = SyncState::load();
+    if index >= state.conflicts.len() {
+        return Ok(());
+    }
+    let conflict = state.conflicts.remove(index);
+    if conflict.kept_local != keep_local {
+        let chosen = if keep_local { conflict.local } else { conflict.remote };
+        let mut em = EventManager::create();
+        let mut events = em.events.clone();
+        if let Some(se) = events.iter_mut().find(|se| se.uuid.to_string() == conflict.uuid) {
+            let mut fields = event_fields(se);
+            fields.remove(DELETED);
+            fields.insert(conflict.field, chosen.value);
+            fields.insert(String::from("uuid"), Value::String(conflict.uuid));
+            *se = serde_json::from_value(Value::Object(fields))?;
+        }
+        em.set_events(events, "Resolve sync conflict")?;
+    }
+    state.store()?;
+
+    Ok(())
+}
+
+fn short_value(value: &Value) -> String {
+    let raw = value.to_string();
+    match raw.char_indices().nth(60) {
+        Some((i, _)) => format!("{}…", &raw[..i]),
+        None => raw,
+    }
+}
+
+pub struct SyncPanel {
+    settings: SyncSettings,
+    state: SyncState,
+    syncing: bool,
+    status: Option<String>,
+}
+
+pub enum SyncPanelMsg {
+    Save,
+    SyncNow,
+    Synced(Result<SyncState, String>),
+    Resolve(usize, bool), // Conflict index, keep mine.
+}
+
+impl Component for SyncPanel {
+    type Message = SyncPanelMsg;
+    type Properties = ();
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self {
+            settings: SyncSettings::load(),
+            state: SyncState::load(),
+            syncing: false,
+            status: None,
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            SyncPanelMsg::Save => {
+                let value = |id: &str| UiHelpers::get_value_from_input_by_id(id).unwrap_or_default();
+                self.settings = SyncSettings {
+                    url: value("#syncUrl"),
+                    user: value("#syncUser"),
+                    token: value("#syncToken"),
+                };
+                self.status = match self.settings.store() {
+                    Ok(_) => Some(String::from("Saved.")),
+                    Err(e) => Some(format!("Couldn't save settings: {e}")),
+                };
+            }
+            SyncPanelMsg::SyncNow => {
+                self.syncing = true;
+                self.status = None;
+                ctx.link().send_future(async { SyncPanelMsg::Synced(sync_now().await) });
+            }
+            SyncPanelMsg::Synced(result) => {
+                self.syncing = false;
+                self.status = match result {
+                    Ok(state) => {
+                        self.state = state;
+                        Some(format!(
+                            "Synced {} events, {} conflict(s).",
+                            self.state.records.iter().filter(|r| !r.deleted()).count(),
+                            self.state.conflicts.len()
+                        ))
+                    }
+                    Err(e) => {
+                        console_log!(format!("BYNGER - Sync Failed - {e}"));
+                        Some(format!("Sync failed: {e}"))
+                    }
+                };
+            }
+            SyncPanelMsg::Resolve(index, keep_local) => {
+                if let Err(e) = resolve_conflict(index, keep_local) {
+                    console_log!(format!("BYNGER - Resolve Conflict Failed - {e}"));
+                }
+                self.state = SyncState::load();
+                // Get the choice out to the server (and other devices) straight away.
+                ctx.link().send_message(SyncPanelMsg::SyncNow);
+            }
+        }
+
+        true
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let link = ctx.link();
+        let tz = UserTimeZone::get();
+        let stamp = |ms: i64| {
+            Utc.timestamp_millis_opt(ms)
+                .single()
+                .map(|dt| dt.with_timezone(&tz).format("%a %b %d %Y %R").to_string())
+                .unwrap_or_default()
+        };
+        let events = EventManager::create().events;
+        let conflicts = self
+            .state
+            .conflicts
+            .iter()
+            .enumerate()
+            .map(|(index, c)| {
+                let title = Uuid::parse_str(&c.uuid)
+                    .ok()
+                    .and_then(|id| events.iter().find(|se| se.uuid == id))
+                    .map_or_else(|| c.uuid.clone(), short_title);
+                let kept = if c.kept_local { "Kept mine" } else { "Kept theirs" };
+                html! {
+                    <tr>
+                        <td>{title}</td>
+                        <td>{&c.field}</td>
+                        <td title={stamp(c.local.modified)}>{short_value(&c.local.value)}</td>
+                        <td title={stamp(c.remote.modified)}>{short_value(&c.remote.value)}</td>
+                        <td>{kept}</td>
+                        <td>
+                            <div class="buttons are-small">
+                                <button class="button" onclick={link.callback(move |_| SyncPanelMsg::Resolve(index, true))}>
+                                    {"Keep Mine"}
+                                </button>
+                                <button class="button" onclick={link.callback(move |_| SyncPanelMsg::Resolve(index, false))}>
+                                    {"Keep Theirs"}
+                                </button>
+                            </div>
+                        </td>
+                    </tr>
+                }
+            })
+            .collect::<Html>();
+
+        let mut sync_class = classes!("button", "is-success");
+        if self.syncing {
+            sync_class.push("is-loading");
+        }
+
+        html! {
+            <div class="box sync-panel">
+                <h1 class="title is-4">{"Sync"}</h1>
+                <p class="mb-3">{"Keep your schedule in step across devices with a Bynger sync server."}</p>
+                <div class="field is-grouped is-grouped-multiline">
+                    <p class="control is-expanded">
+                        <input class="input" id="syncUrl" type="url" placeholder="http://127.0.0.1:8787"
+                            value={self.settings.url.clone()} />
+                    </p>
+                    <p class="control">
+                        <input class="input" id="syncUser" type="text" placeholder="User"
+                            value={self.settings.user.clone()} />
+                    </p>
+                    <p class="control">
+                        <input class="input" id="syncToken" type="password" placeholder="Token (optional)"
+                            value={self.settings.token.clone()} />
+                    </p>
+                    <p class="control">
+                        <button class="button" onclick={link.callback(|_| SyncPanelMsg::Save)}>{"Save"}</button>
+                    </p>
+                    <p class="control">
+                        <button class={sync_class} disabled={!self.settings.configured()}
+                            onclick={link.callback(|_| SyncPanelMsg::SyncNow)}>{"Sync Now"}</button>
+                    </p>
+                </div>
+                <p class="is-size-7">
+                    if self.state.last_synced > 0 {
+                        {format!("Last synced {}.", stamp(self.state.last_synced))}
+                    } else {
+                        {"Never synced."}
+                    }
+                </p>
+                if let Some(status) = &self.status {
+                    <p class="mt-2">{status}</p>
+                }
+                if !self.state.conflicts.is_empty() {
+                    <h2 class="subtitle mt-4">{"Conflicts"}</h2>
+                    <p class="is-size-7 mb-2">{"Both sides changed these since the last sync, the newest change won."}</p>
+                    <table class="table is-fullwidth is-narrow sync-conflicts">
+                        <thead>
+                            <tr>
+                                <th>{"Event"}</th><th>{"Field"}</th><th>{"Mine"}</th>
+                                <th>{"Theirs"}</th><th>{"Result"}</th><th></th>
+                            </tr>
+                        </thead>
+                        <tbody>{conflicts}</tbody>
+                    </table>
+                }
+            </div>
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schedule_show::Movie;
+    use crate::search_client::MediaType;
+
+    fn event(n: u128, hour: i64) -> ScheduledEvent {
+        ScheduledEvent {
+            uuid: Uuid::from_u128(n),
+            scheduled_date: Utc.timestamp_millis_opt(hour * 3_600_000).unwrap(),
+            media_type: MediaType::movie,
+            episode: None,
+            movie: Some(Movie {
+                release_date: String::from("2023-01-01"),
+                show_name: format!("Movie {n}"),
+                id: 0,
+                movie_id: n as usize,
+                runtime: 90,
+            }),
+            calendar: Uuid::nil(),
+            party: None,
+            tags: vec![],
+            watch_log: vec![],
+            legacy_watched: false,
+        }
+    }
+
+    fn stamp(records: &[SyncRecord], n: u128, field: &str) -> i64 {
+        let uuid = Uuid::from_u128(n).to_string();
+        records.iter().find(|r| r.uuid == uuid).unwrap().fields[field].modified
+    }
+
+    fn events_of(records: &[SyncRecord]) -> Vec<ScheduledEvent> {
+        records.iter().filter_map(event_from_record).collect()
+    }
+
+    #[test]
+    fn records_round_trip_to_events() {
+        let se = event(1, 10);
+        let records = local_records(std::slice::from_ref(&se), &[], &EditTimes::default(), 5);
+
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].uuid, se.uuid.to_string());
+        assert!(!records[0].deleted());
+        assert_eq!(events_of(&records), [se]);
+    }
+
+    #[test]
+    fn edit_times_stamp_only_what_changed() {
+        let mut edits = EditTimes::default();
+        let a = event(1, 10);
+        edits.record(&[], std::slice::from_ref(&a), 100);
+        let a_id = a.uuid.to_string();
+        assert_eq!(edits.modified(&a_id, "scheduled_date"), Some(100));
+        assert_eq!(edits.modified(&a_id, "movie"), Some(100));
+        assert_eq!(edits.modified(&a_id, DELETED), Some(100));
+
+        // Moving it only restamps the date, and a new event gets stamped as a whole.
+        let moved = ScheduledEvent { scheduled_date: event(1, 20).scheduled_date, ..a.clone() };
+        let b = event(2, 30);
+        edits.record(std::slice::from_ref(&a), &[moved.clone(), b.clone()], 200);
+        assert_eq!(edits.modified(&a_id, "scheduled_date"), Some(200));
+        assert_eq!(edits.modified(&a_id, "movie"), Some(100));
+        assert_eq!(edits.modified(&b.uuid.to_string(), "movie"), Some(200));
+
+        // Unchanged events are left alone, removed ones get a delete stamp.
+        edits.record(&[moved, b.clone()], std::slice::from_ref(&b), 300);
+        assert_eq!(edits.modified(&a_id, DELETED), Some(300));
+        assert_eq!(edits.modified(&b.uuid.to_string(), "movie"), Some(200));
+    }
+
+    #[test]
+    fn changed_fields_take_their_edit_time() {
+        let a = event(1, 10);
+        let snapshot = local_records(std::slice::from_ref(&a), &[], &EditTimes::default(), 100);
+        let moved = ScheduledEvent { scheduled_date: event(1, 20).scheduled_date, ..a.clone() };
+
+        // Unchanged fields keep the snapshot's stamp, the moved date takes when it was moved.
+        let mut edits = EditTimes::default();
+        edits.record(&[a], std::slice::from_ref(&moved), 200);
+        let records = local_records(std::slice::from_ref(&moved), &snapshot, &edits, 300);
+        assert_eq!(stamp(&records, 1, "movie"), 100);
+        assert_eq!(stamp(&records, 1, "scheduled_date"), 200);
+
+        // Without an edit time it's stamped as of the sync.
+        let records = local_records(&[moved], &snapshot, &EditTimes::default(), 300);
+        assert_eq!(stamp(&records, 1, "scheduled_date"), 300);
+    }
+
+    #[test]
+    fn removed_events_become_tombstones() {
+        let (a, b) = (event(1, 10), event(2, 20));
+        let mut snapshot = local_records(&[a.clone(), b.clone()], &[], &EditTimes::default(), 100);
+        // Something we never could read shouldn't be deleted on everyone else's devices.
+        snapshot.push(SyncRecord { uuid: String::from("unreadable"), fields: BTreeMap::new() });
+        let mut edits = EditTimes::default();
+        edits.record(&[a.clone(), b.clone()], std::slice::from_ref(&b), 200);
+
+        let records = local_records(&[b], &snapshot, &edits, 300);
+        assert_eq!(records.len(), 2);
+        let tombstone = records.iter().find(|r| r.uuid == a.uuid.to_string()).unwrap();
+        assert!(tombstone.deleted());
+        assert_eq!(tombstone.fields[DELETED].modified, 200);
+
+        // Already deleted records don't get deleted again.
+        let records = local_records(&[], &records, &EditTimes::default(), 400);
+        assert!(!records.iter().any(|r| r.uuid == a.uuid.to_string()));
+    }
+
+    #[test]
+    fn edits_made_mid_sync_survive_the_response() {
+        let (a, b) = (event(1, 10), event(2, 20));
+        let snapshot = local_records(&[a.clone(), b.clone()], &[], &EditTimes::default(), 100);
+
+        // The server hands back what we sent, plus another device moving b and adding c.
+        let mut response = snapshot.clone();
+        let other = local_records(&[a.clone(), event(2, 25), event(3, 30)], &snapshot, &EditTimes::default(), 150);
+        merge(&mut response, other, 100);
+
+        // While that was in flight a was moved here and b was deleted.
+        let moved = ScheduledEvent { scheduled_date: event(1, 40).scheduled_date, ..a.clone() };
+        let mut edits = EditTimes::default();
+        edits.record(&[a.clone(), b.clone()], std::slice::from_ref(&moved), 120);
+
+        let records = catch_up_records(&response, std::slice::from_ref(&moved), &snapshot, &edits, 200);
+        let events = events_of(&records);
+        assert!(events.contains(&moved));
+        assert!(events.contains(&event(3, 30)));
+        // Deleting is its own field, so their move doesn't bring b back.
+        assert!(!events.iter().any(|se| se.uuid == b.uuid));
+        assert_eq!(events.len(), 2);
+    }
+}
@@ -0,0 +1,127 @@
+use std::collections::BTreeMap;
+
+use gloo::storage::errors::StorageError;
+use gloo::storage::{LocalStorage, Storage};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use yew::prelude::*;
+
+use crate::events::ScheduledEvent;
+use crate::site_config::ByngerStore;
+
+// Tags without a color of their own get one of these, picked by name so it doesn't jump around.
+const PALETTE: [&str; 8] = [
+    "#3e8ed0", "#48c78e", "#f14668", "#ffb70f", "#9b59b6", "#00d1b2", "#e67e22", "#7a7a7a",
+];
+
+// Tags are kept lower case and trimmed so "Horror" and "horror " are the same tag.
+pub fn normalize(tag: &str) -> String {
+    tag.trim().to_lowercase()
+}
+
+// From a comma separated input, e.g. "horror, Comfort".
+pub fn parse_tags(raw: &str) -> Vec<String> {
+    raw.split(',')
+        .map(normalize)
+        .filter(|tag| !tag.is_empty())
+        .unique()
+        .collect()
+}
+
+// Shows are keyed like "tv:1396" or "movie:603", the same id can be both.
+pub fn show_key(se: &ScheduledEvent) -> Option<String> {
+    match (&se.episode, &se.movie) {
+        (Some(ep), _) => Some(format!("tv:{}", ep.show_id)),
+        (_, Some(mv)) => Some(format!("movie:{}", mv.movie_id)),
+        _ => None,
+    }
+}
+
+// Tags on whole shows, and the color each tag is drawn in.
+// Tags on single events live on the event itself.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Tags {
+    #[serde(default)]
+    pub shows: BTreeMap<String, Vec<String>>,
+    #[serde(default)]
+    pub colors: BTreeMap<String, String>,
+}
+
+impl Tags {
+    pub fn load() -> Self {
+        LocalStorage::get(ByngerStore::Tags.to_string()).unwrap_or_default()
+    }
+
+    pub fn store(&self) -> Result<(), StorageError> {
+        LocalStorage::set(ByngerStore::Tags.to_string(), self)
+    }
+
+    pub fn show_tags(&self, se: &ScheduledEvent) -> Vec<String> {
+        show_key(se)
+            .and_then(|key| self.shows.get(&key).cloned())
+            .unwrap_or_default()
+    }
+
+    pub fn set_show_tags(&mut self, key: String, tags: Vec<String>) {
+        if tags.is_empty() {
+            self.shows.remove(&key);
+        } else {
+            self.shows.insert(key, tags);
+        }
+    }
+
+    // The show's tags then the event's own, without repeats.
+    pub fn event_tags(&self, se: &ScheduledEvent) -> Vec<String> {
+        self.show_tags(se)
+            .into_iter()
+            .chain(se.tags.iter().cloned())
+            .unique()
+            .collect()
+    }
+
+    pub fn has_tag(&self, se: &ScheduledEvent, tag: &str) -> bool {
+        self.event_tags(se).iter().any(|t| t == tag)
+    }
+
+    // Every tag in use, for filters.
+    pub fn all_tags(&self, events: &[ScheduledEvent]) -> Vec<String> {
+        self.shows
+            .values()
+            .flatten()
+            .chain(events.iter().flat_map(|se| se.tags.iter()))
+            .cloned()
+            .unique()
+            .sorted()
+            .collect()
+    }
+
+    pub fn color(&self, tag: &str) -> String {
+        self.colors.get(tag).cloned().unwrap_or_else(|| {
+            let hash = tag.bytes().fold(0usize, |h, b| h.wrapping_mul(31).wrapping_add(b as usize));
+            PALETTE[hash % PALETTE.len()].to_string()
+        })
+    }
+
+    // An event is drawn in the color of its first tag.
+    pub fn event_color(&self, se: &ScheduledEvent) -> Option<String> {
+        self.event_tags(se).first().map(|tag| self.color(tag))
+    }
+
+    pub fn set_color(&mut self, tag: &str, color: String) {
+        self.colors.insert(normalize(tag), color);
+    }
+}
+
+// Little colored chips, one per tag.
+pub fn tag_chips(tags: &Tags, names: &[String]) -> Html {
+    names
+        .iter()
+        .map(|tag| {
+            html! {
+                <span class="tag is-rounded event-tag" style={format!("background-color: {};", tags.color(tag))}>
+                    {tag}
+                </span>
+            }
+        })
+        .collect::<Html>()
+}
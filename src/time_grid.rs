@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime};
+use chrono_tz::Tz;
+use uuid::Uuid;
+use yew::prelude::*;
+
+use crate::events::ScheduledEvent;
+use crate::tags::Tags;
+
+const MINUTES_PER_DAY: i64 = 24 * 60;
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct TimeGridProps {
+    pub days: Vec<NaiveDate>,
+    pub events: Vec<ScheduledEvent>,
+    pub time_zone: Tz,
+    pub onclick: Callback<Option<ScheduledEvent>>,
+    pub tags: Rc<Tags>,
+    // Calendar colors by event, when more than one calendar is showing.
+    #[prop_or_default]
+    pub colors: HashMap<Uuid, String>,
+}
+
+// The part of an event that falls on one day, in minutes from that day's midnight.
+#[derive(Clone)]
+struct Block {
+    event: ScheduledEvent,
+    start: i64,
+    end: i64,
+    lane: usize,
+    lanes: usize,
+}
+
+// Anything without a runtime still gets a sliver so it can be clicked.
+fn runtime(se: &ScheduledEvent) -> i64 {
+    se.runtime().max(15)
+}
+
+fn title(se: &ScheduledEvent) -> String {
+    match (&se.episode, &se.movie) {
+        (Some(ep), _) => format!(
+            "{} | S{:02}E{:02} {}",
+            ep.show_name, ep.season_number, ep.episode_number, ep.name
+        ),
+        (_, Some(mv)) => mv.show_name.clone(),
+        _ => String::from("Unknown"),
+    }
+}
+
+// Clips every event to the given day and lays overlapping ones out side by side.
+fn day_blocks(day: NaiveDate, events: &[ScheduledEvent], tz: &Tz) -> Vec<Block> {
+    let midnight = NaiveDateTime::new(day, NaiveTime::MIN);
+    let mut blocks = events
+        .iter()
+        .filter_map(|se| {
+            let start = se.scheduled_date.with_timezone(tz).naive_local();
+            let end = start + Duration::minutes(runtime(se));
+            let start = (start - midnight).num_minutes().max(0);
+            let end = (end - midnight).num_minutes().min(MINUTES_PER_DAY);
+
+            (start < end).then(|| Block {
+                event: se.clone(),
+                start,
+                end,
+                lane: 0,
+                lanes: 1,
+            })
+        })
+        .collect::<Vec<Block>>();
+    blocks.sort_by_key(|b| (b.start, b.end));
+
+    // Walk clusters of blocks that overlap one another, each gets as many lanes as it needs.
+    let mut cluster_start = 0;
+    let mut cluster_end = i64::MIN;
+    let mut lane_ends: Vec<i64> = vec![];
+    for i in 0..=blocks.len() {
+        let starts_new_cluster = blocks.get(i).is_none_or(|b| b.start >= cluster_end);
+        if starts_new_cluster {
+            for block in &mut blocks[cluster_start..i] {
+                block.lanes = lane_ends.len().max(1);
+            }
+            cluster_start = i;
+            lane_ends.clear();
+        }
+        if let Some(block) = blocks.get_mut(i) {
+            block.lane = match lane_ends.iter().position(|&end| end <= block.start) {
+                Some(lane) => {
+                    lane_ends[lane] = block.end;
+                    lane
+                }
+                None => {
+                    lane_ends.push(block.end);
+                    lane_ends.len() - 1
+                }
+            };
+            cluster_end = if starts_new_cluster { block.end } else { cluster_end.max(block.end) };
+        }
+    }
+
+    blocks
+}
+
+#[function_component(TimeGrid)]
+pub fn time_grid(props: &TimeGridProps) -> Html {
+    let tz = props.time_zone;
+    let tags = &props.tags;
+    let hours = (0..24)
+        .map(|h| html! { <div class="time-grid-hour">{format!("{h:02}:00")}</div> })
+        .collect::<Html>();
+
+    let columns = props
+        .days
+        .iter()
+        .map(|&day| {
+            let blocks = day_blocks(day, &props.events, &tz)
+                .into_iter()
+                .map(|block| {
+                    let pct = |minutes: i64| minutes as f64 * 100.0 / MINUTES_PER_DAY as f64;
+                    let mut style = format!(
+                        "top: {:.3}%; height: {:.3}%; left: {:.2}%; width: {:.2}%;",
+                        pct(block.start),
+                        pct(block.end - block.start),
+                        block.lane as f64 * 100.0 / block.lanes as f64,
+                        100.0 / block.lanes as f64,
+                    );
+                    if let Some(color) = tags.event_color(&block.event) {
+                        style.push_str(&format!(" background-color: {color};"));
+                    }
+                    if let Some(color) = props.colors.get(&block.event.uuid) {
+                        style.push_str(&format!(" border-left: 4px solid {color};"));
+                    }
+                    let start = block.event.scheduled_date.with_timezone(&tz);
+                    let label = format!("{} {}", start.format("%R"), title(&block.event));
+                    let mut class = classes!("time-grid-event");
+                    if block.event.watched() {
+                        class.push("event-watched");
+                    }
+                    if block.event.rewatched() {
+                        class.push("event-rewatched");
+                    }
+                    let oce = props.onclick.clone();
+                    let event = block.event.clone();
+                    let onclick = Callback::from(move |_| oce.emit(Some(event.clone())));
+
+                    html! {
+                        <a {class} {style} {onclick} title={label.clone()}>{label}</a>
+                    }
+                })
+                .collect::<Html>();
+
+            html! {
+                <div class="time-grid-day">
+                    <div class="time-grid-day-header">{day.format("%a %d").to_string()}</div>
+                    <div class="time-grid-day-body">{blocks}</div>
+                </div>
+            }
+        })
+        .collect::<Html>();
+
+    html! {
+        <div class="time-grid">
+            <div class="time-grid-hours">
+                <div class="time-grid-day-header"></div>
+                {hours}
+            </div>
+            {columns}
+        </div>
+    }
+}
@@ -0,0 +1,45 @@
+use chrono::{DateTime, Duration, LocalResult, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use gloo::storage::{LocalStorage, Storage};
+use js_sys::{Array, Intl, Object, Reflect};
+use std::ops::Add;
+use wasm_bindgen::JsValue;
+
+use crate::site_config::ByngerStore;
+
+pub struct UserTimeZone;
+
+impl UserTimeZone {
+    // The zone the browser reports, e.g. "America/Chicago".
+    pub fn browser() -> Option<Tz> {
+        let options = Intl::DateTimeFormat::new(&Array::new(), &Object::new()).resolved_options();
+        Reflect::get(&options, &JsValue::from_str("timeZone"))
+            .ok()?
+            .as_string()?
+            .parse()
+            .ok()
+    }
+
+    // The configured zone, falling back to the browser's and then UTC.
+    pub fn get() -> Tz {
+        LocalStorage::get::<String>(ByngerStore::TimeZone.to_string())
+            .ok()
+            .and_then(|tz| tz.parse().ok())
+            .or_else(Self::browser)
+            .unwrap_or(Tz::UTC)
+    }
+
+    // Converts a wall clock time in the given zone to UTC.
+    // Ambiguous times (clocks falling back) take the earlier instant and times skipped over
+    // (clocks springing forward) move ahead to the first time that actually exists.
+    pub fn to_utc(tz: &Tz, local: NaiveDateTime) -> DateTime<Utc> {
+        let mut candidate = local;
+        loop {
+            match tz.from_local_datetime(&candidate) {
+                LocalResult::Single(dt) => return dt.with_timezone(&Utc),
+                LocalResult::Ambiguous(earliest, _) => return earliest.with_timezone(&Utc),
+                LocalResult::None => candidate = candidate.add(Duration::minutes(15)),
+            }
+        }
+    }
+}
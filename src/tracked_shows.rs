@@ -0,0 +1,62 @@
+use std::collections::BTreeSet;
+
+use gloo::storage::errors::StorageError;
+use gloo::storage::{LocalStorage, Storage};
+use serde::{Deserialize, Serialize};
+
+use crate::schedule_show::SchedulingOptions;
+use crate::site_config::ByngerStore;
+
+// A show that's still airing, remembered so newly aired episodes can be offered for scheduling.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrackedShow {
+    pub show_id: usize,
+    pub show_name: String,
+    pub fallback_runtime: usize, // in Minutes, for episodes TMDB doesn't have a runtime for yet.
+    pub options: SchedulingOptions, // Last used options for this show.
+    // Episodes already scheduled, or aired and offered, so only new ones are proposed.
+    pub known_episodes: BTreeSet<usize>,
+}
+
+pub struct TrackedShowManager {
+    storage: String,
+    pub shows: Vec<TrackedShow>,
+}
+
+impl TrackedShowManager {
+    pub(crate) fn create() -> Self {
+        let storage = format!("{}", ByngerStore::TrackedShows);
+        let shows = LocalStorage::get(&*storage).unwrap_or_default();
+
+        TrackedShowManager { storage, shows }
+    }
+
+    fn store(&self) -> Result<(), StorageError> {
+        LocalStorage::set(&self.storage, self.shows.to_vec())
+    }
+
+    // Start (or refresh) tracking a show, keeping anything we already knew about it.
+    pub fn track(&mut self, mut show: TrackedShow) -> Result<(), StorageError> {
+        if let Some(pos) = self.shows.iter().position(|ts| ts.show_id == show.show_id) {
+            let existing = self.shows.remove(pos);
+            show.known_episodes.extend(existing.known_episodes);
+        }
+        self.shows.push(show);
+
+        self.store()
+    }
+
+    pub fn untrack(&mut self, show_id: usize) -> Result<(), StorageError> {
+        self.shows.retain(|ts| ts.show_id != show_id);
+
+        self.store()
+    }
+
+    pub fn mark_known(&mut self, show_id: usize, episode_ids: &[usize]) -> Result<(), StorageError> {
+        if let Some(pos) = self.shows.iter().position(|ts| ts.show_id == show_id) {
+            self.shows[pos].known_episodes.extend(episode_ids);
+        }
+
+        self.store()
+    }
+}
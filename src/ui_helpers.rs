@@ -1,7 +1,7 @@
 use crate::search_client::TMDB;
 use wasm_bindgen::UnwrapThrowExt;
 use wasm_bindgen::{JsCast, JsValue};
-use web_sys::{Event, HtmlElement, HtmlInputElement, InputEvent};
+use web_sys::{Event, HtmlElement, HtmlInputElement, HtmlSelectElement, InputEvent};
 use yew::{html, Html};
 
 pub struct UiHelpers;
@@ -44,6 +44,15 @@ impl UiHelpers {
         target.value()
     }
 
+    // Value of whichever input or select fired the event.
+    pub fn get_value_from_event(e: &Event) -> Option<String> {
+        let target = e.target()?;
+        match target.dyn_ref::<HtmlSelectElement>() {
+            Some(select) => Some(select.value()),
+            None => target.dyn_ref::<HtmlInputElement>().map(|input| input.value()),
+        }
+    }
+
     pub fn get_thumbnail(path: Option<String>) -> Html {
         match TMDB::poster_path(path) {
             None => html! {},
@@ -0,0 +1,287 @@
+use chrono::{DateTime, Utc};
+use gloo::storage::errors::StorageError;
+use gloo::storage::{LocalStorage, Storage};
+use serde::{Deserialize, Serialize};
+use weblog::console_error;
+use yew::prelude::*;
+
+use crate::schedule_show::ScheduleShow;
+use crate::search_client::MediaType;
+use crate::show_card::Show;
+use crate::site_config::ByngerStore;
+use crate::ui_helpers::UiHelpers;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Priority {
+    High,
+    #[default]
+    Normal,
+    Low,
+}
+
+impl Priority {
+    const ALL: [Priority; 3] = [Priority::High, Priority::Normal, Priority::Low];
+
+    fn label(&self) -> &'static str {
+        match self {
+            Priority::High => "High",
+            Priority::Normal => "Normal",
+            Priority::Low => "Low",
+        }
+    }
+}
+
+// A show or movie parked for later, with just enough of the Show to list it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WatchlistEntry {
+    pub show_id: String,
+    pub media_type: MediaType,
+    pub title: String,
+    pub poster: Option<String>,
+    pub first_air_date: Option<String>,
+    #[serde(default)]
+    pub notes: String,
+    #[serde(default)]
+    pub priority: Priority,
+    pub added: DateTime<Utc>,
+}
+
+impl From<&Show> for WatchlistEntry {
+    fn from(show: &Show) -> Self {
+        WatchlistEntry {
+            show_id: show.id.clone(),
+            media_type: show.media_type.clone(),
+            title: show.title.clone().unwrap_or_default(),
+            poster: show.poster.clone(),
+            first_air_date: show.first_air_date.clone(),
+            notes: String::new(),
+            priority: Priority::default(),
+            added: Utc::now(),
+        }
+    }
+}
+
+// Entries are kept in the order the user arranged them.
+pub struct WatchlistManager {
+    storage: String,
+    pub entries: Vec<WatchlistEntry>,
+}
+
+impl WatchlistManager {
+    pub(crate) fn create() -> Self {
+        let storage = format!("{}", ByngerStore::Watchlist);
+        let entries = LocalStorage::get(&*storage).unwrap_or_default();
+
+        WatchlistManager { storage, entries }
+    }
+
+    fn store(&self) -> Result<(), StorageError> {
+        LocalStorage::set(&self.storage, self.entries.to_vec())
+    }
+
+    fn position(&self, show_id: &str, media_type: &MediaType) -> Option<usize> {
+        self.entries
+            .iter()
+            .position(|e| e.show_id == show_id && e.media_type == *media_type)
+    }
+
+    pub fn contains(&self, show_id: &str, media_type: &MediaType) -> bool {
+        self.position(show_id, media_type).is_some()
+    }
+
+    // Adding something that's already listed leaves the existing entry alone.
+    pub fn add(&mut self, entry: WatchlistEntry) -> Result<(), StorageError> {
+        if !self.contains(&entry.show_id, &entry.media_type) {
+            self.entries.push(entry);
+        }
+
+        self.store()
+    }
+
+    pub fn remove(&mut self, index: usize) -> Result<(), StorageError> {
+        if index < self.entries.len() {
+            self.entries.remove(index);
+        }
+
+        self.store()
+    }
+
+    // Moves an entry up (negative) or down (positive) the list.
+    pub fn reorder(&mut self, index: usize, by: isize) -> Result<(), StorageError> {
+        let target = index as isize + by;
+        if index < self.entries.len() && (0..self.entries.len() as isize).contains(&target) {
+            let entry = self.entries.remove(index);
+            self.entries.insert(target as usize, entry);
+        }
+
+        self.store()
+    }
+
+    // Highest priority first, keeping the user's order within each priority.
+    pub fn sort_by_priority(&mut self) -> Result<(), StorageError> {
+        self.entries.sort_by_key(|e| e.priority);
+
+        self.store()
+    }
+
+    pub fn update(&mut self, index: usize, notes: String, priority: Priority) -> Result<(), StorageError> {
+        if let Some(entry) = self.entries.get_mut(index) {
+            entry.notes = notes;
+            entry.priority = priority;
+        }
+
+        self.store()
+    }
+
+    // Drops an entry once it's made it on to the calendar.
+    pub fn remove_show(&mut self, show_id: &str, media_type: &MediaType) -> Result<(), StorageError> {
+        if let Some(index) = self.position(show_id, media_type) {
+            self.entries.remove(index);
+        }
+
+        self.store()
+    }
+}
+
+pub struct Watchlist {
+    scheduling: Option<(String, MediaType)>,
+}
+
+pub enum WatchlistMsg {
+    Remove(usize),
+    Reorder(usize, isize),
+    SortByPriority,
+    Update(usize),
+    Schedule(usize),
+    Scheduled,
+    ScheduleClosed,
+}
+
+impl Component for Watchlist {
+    type Message = WatchlistMsg;
+    type Properties = ();
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self { scheduling: None }
+    }
+
+    fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
+        let mut wm = WatchlistManager::create();
+        let stored = match msg {
+            WatchlistMsg::Remove(index) => wm.remove(index),
+            WatchlistMsg::Reorder(index, by) => wm.reorder(index, by),
+            WatchlistMsg::SortByPriority => wm.sort_by_priority(),
+            WatchlistMsg::Update(index) => {
+                let notes = UiHelpers::get_value_from_input_by_id(&format!("#watchlistNotes{index}"))
+                    .unwrap_or_default();
+                let priority = UiHelpers::get_value_from_input_by_id(&format!("#watchlistPriority{index}"))
+                    .and_then(|raw| Priority::ALL.into_iter().find(|p| p.label() == raw))
+                    .unwrap_or_default();
+                wm.update(index, notes, priority)
+            }
+            WatchlistMsg::Schedule(index) => {
+                self.scheduling = wm
+                    .entries
+                    .get(index)
+                    .map(|e| (e.show_id.clone(), e.media_type.clone()));
+                Ok(())
+            }
+            WatchlistMsg::Scheduled => match &self.scheduling {
+                Some((show_id, media_type)) => wm.remove_show(show_id, media_type),
+                None => Ok(()),
+            },
+            WatchlistMsg::ScheduleClosed => {
+                self.scheduling = None;
+                Ok(())
+            }
+        };
+        if let Err(e) = stored {
+            console_error!(format!("BYNGER - Watchlist Update Failed - {}", e));
+        }
+
+        true
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let link = ctx.link();
+        let wm = WatchlistManager::create();
+        let last = wm.entries.len().saturating_sub(1);
+        let entries = wm
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(index, entry)| {
+                let icon = match entry.media_type {
+                    MediaType::movie => "gg-film",
+                    _ => "gg-tv",
+                };
+                let onupdate = link.callback(move |_: Event| WatchlistMsg::Update(index));
+
+                html! {
+                    <div class="media watchlist-entry" key={format!("{}_{}", entry.media_type, entry.show_id)}>
+                        <div class="media-left">
+                            {UiHelpers::get_thumbnail(entry.poster.clone())}
+                        </div>
+                        <div class="media-content">
+                            <p class="is-size-5">
+                                <span class="icon"><i class={icon}></i></span>
+                                {&entry.title}
+                                <span class="is-size-7 ml-2">{entry.first_air_date.clone().unwrap_or_default()}</span>
+                            </p>
+                            <div class="field is-grouped">
+                                <p class="control">
+                                    <span class="select is-small">
+                                        <select id={format!("watchlistPriority{index}")} onchange={onupdate.clone()}>
+                                            {Priority::ALL.iter().map(|p| html!{
+                                                <option value={p.label()} selected={*p == entry.priority}>{p.label()}</option>
+                                            }).collect::<Html>()}
+                                        </select>
+                                    </span>
+                                </p>
+                                <p class="control is-expanded">
+                                    <input class="input is-small" type="text" placeholder="Notes"
+                                        id={format!("watchlistNotes{index}")} value={entry.notes.clone()} onchange={onupdate} />
+                                </p>
+                            </div>
+                            <div class="buttons are-small">
+                                <button class="button is-success" onclick={link.callback(move |_| WatchlistMsg::Schedule(index))}>
+                                    {"Schedule"}
+                                </button>
+                                <button class="button" disabled={index == 0}
+                                    onclick={link.callback(move |_| WatchlistMsg::Reorder(index, -1))}>{"Up"}</button>
+                                <button class="button" disabled={index == last}
+                                    onclick={link.callback(move |_| WatchlistMsg::Reorder(index, 1))}>{"Down"}</button>
+                                <button class="button is-danger is-outlined"
+                                    onclick={link.callback(move |_| WatchlistMsg::Remove(index))}>{"Remove"}</button>
+                            </div>
+                        </div>
+                    </div>
+                }
+            })
+            .collect::<Html>();
+
+        html! {
+            <>
+            <div class="box watchlist">
+                <nav class="level">
+                    <p class="level-left title is-4">{"Watchlist"}</p>
+                    <p class="level-right">
+                        <button class="button is-small" onclick={link.callback(|_| WatchlistMsg::SortByPriority)}>
+                            {"Sort By Priority"}
+                        </button>
+                    </p>
+                </nav>
+                if wm.entries.is_empty() {
+                    <p>{"Nothing here yet, add shows from search with \"Watchlist\"."}</p>
+                }
+                {entries}
+            </div>
+            if let Some((show_id, media_type)) = self.scheduling.clone() {
+                <ScheduleShow {show_id} {media_type}
+                    on_scheduled={link.callback(|_| WatchlistMsg::Scheduled)}
+                    on_cancel={link.callback(|_| WatchlistMsg::ScheduleClosed)} />
+            }
+            </>
+        }
+    }
+}
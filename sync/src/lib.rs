@@ -0,0 +1,240 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+// Shared by the app and the sync server so both sides agree on the wire format.
+//
+// Records are kept field by field, each field stamped with when it last changed,
+// so two devices editing different parts of the same event don't trample each other.
+
+pub const DELETED: &str = "deleted";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Stamped {
+    pub value: Value,
+    pub modified: i64, // Unix time in milliseconds, from the device that made the change.
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SyncRecord {
+    pub uuid: String,
+    pub fields: BTreeMap<String, Stamped>,
+}
+
+impl SyncRecord {
+    // Deleting is just another field, so a delete and an edit can be merged like any two edits.
+    pub fn deleted(&self) -> bool {
+        self.fields
+            .get(DELETED)
+            .is_some_and(|s| s.value == Value::Bool(true))
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SyncRequest {
+    pub since: i64, // When this device last synced, 0 for never.
+    pub records: Vec<SyncRecord>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SyncResponse {
+    pub records: Vec<SyncRecord>,
+    pub conflicts: Vec<Conflict>,
+}
+
+// Both sides changed the same field since the last sync, the newer one won but it's worth a look.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Conflict {
+    pub uuid: String,
+    pub field: String,
+    pub local: Stamped,  // What the syncing device sent.
+    pub remote: Stamped, // What the server had.
+    pub kept_local: bool,
+}
+
+// Merges incoming records in to the stored ones, last writer wins per field.
+// Ties keep what's stored so a resend of the same data is a no-op.
+pub fn merge(stored: &mut Vec<SyncRecord>, incoming: Vec<SyncRecord>, since: i64) -> Vec<Conflict> {
+    let mut conflicts = vec![];
+    for record in incoming {
+        let Some(existing) = stored.iter_mut().find(|r| r.uuid == record.uuid) else {
+            stored.push(record);
+            continue;
+        };
+        for (field, local) in record.fields {
+            match existing.fields.get(&field) {
+                None => {
+                    existing.fields.insert(field, local);
+                }
+                Some(remote) if remote.value == local.value => {}
+                Some(remote) => {
+                    let kept_local = local.modified > remote.modified;
+                    if local.modified > since && remote.modified > since {
+                        conflicts.push(Conflict {
+                            uuid: record.uuid.clone(),
+                            field: field.clone(),
+                            local: local.clone(),
+                            remote: remote.clone(),
+                            kept_local,
+                        });
+                    }
+                    if kept_local {
+                        existing.fields.insert(field, local);
+                    }
+                }
+            }
+        }
+    }
+
+    conflicts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn record(uuid: &str, fields: &[(&str, Value, i64)]) -> SyncRecord {
+        SyncRecord {
+            uuid: uuid.to_string(),
+            fields: fields
+                .iter()
+                .map(|(field, value, modified)| {
+                    (
+                        field.to_string(),
+                        Stamped {
+                            value: value.clone(),
+                            modified: *modified,
+                        },
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    fn value(records: &[SyncRecord], uuid: &str, field: &str) -> Value {
+        records.iter().find(|r| r.uuid == uuid).unwrap().fields[field]
+            .value
+            .clone()
+    }
+
+    #[test]
+    fn new_records_and_fields_are_added() {
+        let mut stored = vec![record("a", &[("title", json!("A"), 10)])];
+        let conflicts = merge(
+            &mut stored,
+            vec![
+                record("a", &[("notes", json!("hi"), 20)]),
+                record("b", &[("title", json!("B"), 20)]),
+            ],
+            0,
+        );
+
+        assert!(conflicts.is_empty());
+        assert_eq!(stored.len(), 2);
+        assert_eq!(value(&stored, "a", "title"), json!("A"));
+        assert_eq!(value(&stored, "a", "notes"), json!("hi"));
+        assert_eq!(value(&stored, "b", "title"), json!("B"));
+    }
+
+    #[test]
+    fn last_writer_wins_per_field() {
+        let mut stored = vec![record(
+            "a",
+            &[("title", json!("old"), 10), ("date", json!("new"), 30)],
+        )];
+        merge(
+            &mut stored,
+            vec![record(
+                "a",
+                &[("title", json!("new"), 20), ("date", json!("old"), 20)],
+            )],
+            0,
+        );
+
+        assert_eq!(value(&stored, "a", "title"), json!("new"));
+        assert_eq!(value(&stored, "a", "date"), json!("new"));
+    }
+
+    #[test]
+    fn ties_keep_what_is_stored() {
+        let mut stored = vec![record("a", &[("title", json!("stored"), 10)])];
+        let conflicts = merge(
+            &mut stored,
+            vec![record("a", &[("title", json!("sent"), 10)])],
+            0,
+        );
+
+        assert_eq!(value(&stored, "a", "title"), json!("stored"));
+        assert_eq!(conflicts.len(), 1);
+        assert!(!conflicts[0].kept_local);
+    }
+
+    #[test]
+    fn conflicts_only_when_both_sides_changed_since_last_sync() {
+        // The server's change predates our last sync, so ours simply replaces it.
+        let mut stored = vec![record("a", &[("title", json!("theirs"), 5)])];
+        let conflicts = merge(
+            &mut stored,
+            vec![record("a", &[("title", json!("mine"), 20)])],
+            10,
+        );
+        assert!(conflicts.is_empty());
+        assert_eq!(value(&stored, "a", "title"), json!("mine"));
+
+        // Both changed after it, the newer one wins but it's reported.
+        let mut stored = vec![record("a", &[("title", json!("theirs"), 30)])];
+        let conflicts = merge(
+            &mut stored,
+            vec![record("a", &[("title", json!("mine"), 20)])],
+            10,
+        );
+        assert_eq!(value(&stored, "a", "title"), json!("theirs"));
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].field, "title");
+        assert_eq!(conflicts[0].local.value, json!("mine"));
+        assert_eq!(conflicts[0].remote.value, json!("theirs"));
+        assert!(!conflicts[0].kept_local);
+
+        // The same value on both sides is never a conflict.
+        let mut stored = vec![record("a", &[("title", json!("same"), 30)])];
+        let conflicts = merge(
+            &mut stored,
+            vec![record("a", &[("title", json!("same"), 20)])],
+            10,
+        );
+        assert!(conflicts.is_empty());
+    }
+
+    #[test]
+    fn tombstones_merge_like_any_other_field() {
+        let alive = |modified| {
+            record(
+                "a",
+                &[("title", json!("A"), 10), (DELETED, json!(false), modified)],
+            )
+        };
+        let dead = |modified| {
+            record(
+                "a",
+                &[("title", json!("A"), 10), (DELETED, json!(true), modified)],
+            )
+        };
+
+        // A newer delete removes the record.
+        let mut stored = vec![alive(10)];
+        merge(&mut stored, vec![dead(20)], 0);
+        assert!(stored[0].deleted());
+
+        // An edit made after the delete brings it back.
+        let mut stored = vec![dead(20)];
+        merge(&mut stored, vec![alive(30)], 0);
+        assert!(!stored[0].deleted());
+
+        // A stale copy doesn't undo the delete.
+        let mut stored = vec![dead(20)];
+        merge(&mut stored, vec![alive(10)], 0);
+        assert!(stored[0].deleted());
+    }
+}
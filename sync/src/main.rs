@@ -0,0 +1,162 @@
+use std::error::Error;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use bynger_sync::{merge, SyncRecord, SyncRequest, SyncResponse};
+use serde_json::json;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+// Far more than any one person's schedule, so a runaway client can't fill up memory.
+const MAX_BODY: u64 = 16 * 1024 * 1024;
+
+// A tiny self-hostable sync server for Bynger.
+//
+//   bynger-sync-server --addr 127.0.0.1:8787 --data ./bynger-sync-data --token secret
+//
+// GET  /sync/{user} hands back everything stored for that user.
+// POST /sync/{user} merges a SyncRequest in and hands back the merged records plus any conflicts.
+// Each user's records live in {data}/{user}.json.
+
+struct Config {
+    addr: String,
+    data: PathBuf,
+    token: Option<String>, // None = anyone who can reach the server can sync.
+}
+
+impl Config {
+    fn from_args() -> Result<Self, Box<dyn Error>> {
+        let mut config = Config {
+            addr: String::from("127.0.0.1:8787"),
+            data: PathBuf::from("bynger-sync-data"),
+            token: None,
+        };
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or(format!("Missing value for {arg}"));
+            match arg.as_str() {
+                "--addr" => config.addr = value()?,
+                "--data" => config.data = PathBuf::from(value()?),
+                "--token" => config.token = Some(value()?),
+                _ => return Err(format!("Unknown argument {arg}").into()),
+            }
+        }
+
+        Ok(config)
+    }
+}
+
+// Keeps user names to something that's safe to use as a file name.
+fn user_file(data: &Path, user: &str) -> Option<PathBuf> {
+    let valid = !user.is_empty()
+        && user.len() <= 64
+        && user.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+
+    valid.then(|| data.join(format!("{user}.json")))
+}
+
+fn load(path: &Path) -> Result<Vec<SyncRecord>, Box<dyn Error>> {
+    match fs::read_to_string(path) {
+        Ok(raw) => Ok(serde_json::from_str(&raw)?),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(vec![]),
+        Err(e) => Err(e.into()),
+    }
+}
+
+// Written to the side then renamed so a crash can't leave half a file behind.
+fn save(path: &Path, records: &[SyncRecord]) -> Result<(), Box<dyn Error>> {
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, serde_json::to_vec(records)?)?;
+    fs::rename(tmp, path)?;
+
+    Ok(())
+}
+
+fn header(name: &str, value: &str) -> Header {
+    Header::from_bytes(name.as_bytes(), value.as_bytes()).expect("Valid header")
+}
+
+// The app is served from somewhere else, so every response needs CORS headers.
+fn respond(request: Request, status: u16, body: String) {
+    let response = Response::from_string(body)
+        .with_status_code(status)
+        .with_header(header("Content-Type", "application/json"))
+        .with_header(header("Access-Control-Allow-Origin", "*"))
+        .with_header(header("Access-Control-Allow-Methods", "GET, POST, OPTIONS"))
+        .with_header(header("Access-Control-Allow-Headers", "Authorization, Content-Type"));
+    if let Err(e) = request.respond(response) {
+        eprintln!("BYNGER SYNC - Response Failed - {e}");
+    }
+}
+
+fn authorized(request: &Request, token: &Option<String>) -> bool {
+    let Some(token) = token else {
+        return true;
+    };
+    let expected = format!("Bearer {token}");
+
+    request
+        .headers()
+        .iter()
+        .any(|h| h.field.equiv("Authorization") && h.value.as_str() == expected)
+}
+
+fn handle(request: &mut Request, path: &Path) -> Result<(u16, String), Box<dyn Error>> {
+    let mut records = load(path)?;
+    match request.method() {
+        Method::Get => {
+            let response = SyncResponse { records, conflicts: vec![] };
+            Ok((200, serde_json::to_string(&response)?))
+        }
+        Method::Post => {
+            let mut body = String::new();
+            request.as_reader().take(MAX_BODY + 1).read_to_string(&mut body)?;
+            if body.len() as u64 > MAX_BODY {
+                return Ok((413, json!({ "error": "Request too large" }).to_string()));
+            }
+            let sync: SyncRequest = serde_json::from_str(&body)?;
+            let conflicts = merge(&mut records, sync.records, sync.since);
+            save(path, &records)?;
+            let response = SyncResponse { records, conflicts };
+            Ok((200, serde_json::to_string(&response)?))
+        }
+        _ => Ok((405, String::from("{\"error\":\"Method not allowed\"}"))),
+    }
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let config = Config::from_args()?;
+    fs::create_dir_all(&config.data)?;
+    let server = Server::http(&config.addr).map_err(|e| format!("Couldn't listen on {}: {e}", config.addr))?;
+    println!("BYNGER SYNC - Listening on http://{} - Data in {}", config.addr, config.data.display());
+
+    // One request at a time keeps each user's file consistent without any locking.
+    for mut request in server.incoming_requests() {
+        if *request.method() == Method::Options {
+            respond(request, 204, String::new());
+            continue;
+        }
+        if !authorized(&request, &config.token) {
+            respond(request, 401, String::from("{\"error\":\"Unauthorized\"}"));
+            continue;
+        }
+        let path = request
+            .url()
+            .strip_prefix("/sync/")
+            .map(|user| user.trim_end_matches('/'))
+            .and_then(|user| user_file(&config.data, user));
+        let Some(path) = path else {
+            respond(request, 404, String::from("{\"error\":\"Not found\"}"));
+            continue;
+        };
+        match handle(&mut request, &path) {
+            Ok((status, body)) => respond(request, status, body),
+            Err(e) => {
+                eprintln!("BYNGER SYNC - Request Failed - {e}");
+                respond(request, 400, json!({ "error": e.to_string() }).to_string());
+            }
+        }
+    }
+
+    Ok(())
+}
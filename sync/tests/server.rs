@@ -0,0 +1,239 @@
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::thread::sleep;
+use std::time::Duration;
+
+use bynger_sync::{Stamped, SyncRecord, SyncRequest, SyncResponse, DELETED};
+use serde_json::{json, Value};
+
+// Runs the real server binary on a spare local port with its own data directory.
+struct TestServer {
+    addr: String,
+    data: PathBuf,
+    child: Child,
+}
+
+impl TestServer {
+    fn start(token: Option<&str>) -> Self {
+        let addr = TcpListener::bind("127.0.0.1:0")
+            .and_then(|l| l.local_addr())
+            .expect("Free port")
+            .to_string();
+        let data = std::env::temp_dir().join(format!(
+            "bynger-sync-test-{}-{}",
+            std::process::id(),
+            addr.replace([':', '.'], "_")
+        ));
+        let mut command = Command::new(env!("CARGO_BIN_EXE_bynger-sync-server"));
+        command
+            .args(["--addr", &addr, "--data"])
+            .arg(&data)
+            .stdout(Stdio::null());
+        if let Some(token) = token {
+            command.args(["--token", token]);
+        }
+        let child = command.spawn().expect("Server starts");
+
+        for _ in 0..100 {
+            if TcpStream::connect(&addr).is_ok() {
+                break;
+            }
+            sleep(Duration::from_millis(50));
+        }
+
+        TestServer { addr, data, child }
+    }
+
+    // Plain HTTP/1.1 over a socket, enough for what the server speaks.
+    fn request(&self, method: &str, path: &str, token: Option<&str>, body: &str) -> (u16, String) {
+        let mut stream = TcpStream::connect(&self.addr).expect("Connect");
+        let auth = token
+            .map(|t| format!("Authorization: Bearer {t}\r\n"))
+            .unwrap_or_default();
+        write!(
+            stream,
+            "{method} {path} HTTP/1.1\r\nHost: {}\r\n{auth}Content-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            self.addr,
+            body.len()
+        )
+        .expect("Send");
+        let mut raw = String::new();
+        stream.read_to_string(&mut raw).expect("Receive");
+        let (head, body) = raw.split_once("\r\n\r\n").expect("Complete response");
+        let status = head
+            .split_whitespace()
+            .nth(1)
+            .and_then(|s| s.parse().ok())
+            .expect("Status");
+
+        (status, body.to_string())
+    }
+
+    fn sync(&self, user: &str, since: i64, records: Vec<SyncRecord>) -> SyncResponse {
+        let body = serde_json::to_string(&SyncRequest { since, records }).unwrap();
+        let (status, body) = self.request("POST", &format!("/sync/{user}"), None, &body);
+        assert_eq!(status, 200, "{body}");
+
+        serde_json::from_str(&body).expect("SyncResponse")
+    }
+}
+
+impl Drop for TestServer {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        let _ = std::fs::remove_dir_all(&self.data);
+    }
+}
+
+fn record(uuid: &str, fields: &[(&str, Value, i64)]) -> SyncRecord {
+    SyncRecord {
+        uuid: uuid.to_string(),
+        fields: fields
+            .iter()
+            .map(|(field, value, modified)| {
+                (
+                    field.to_string(),
+                    Stamped {
+                        value: value.clone(),
+                        modified: *modified,
+                    },
+                )
+            })
+            .collect(),
+    }
+}
+
+fn value(response: &SyncResponse, uuid: &str, field: &str) -> Value {
+    response
+        .records
+        .iter()
+        .find(|r| r.uuid == uuid)
+        .unwrap()
+        .fields[field]
+        .value
+        .clone()
+}
+
+#[test]
+fn two_clients_sync_through_the_server() {
+    let server = TestServer::start(None);
+
+    // The laptop pushes two events.
+    let laptop = server.sync(
+        "alice",
+        0,
+        vec![
+            record(
+                "a",
+                &[
+                    ("title", json!("Show A"), 100),
+                    (DELETED, json!(false), 100),
+                ],
+            ),
+            record(
+                "b",
+                &[
+                    ("title", json!("Show B"), 100),
+                    (DELETED, json!(false), 100),
+                ],
+            ),
+        ],
+    );
+    assert_eq!(laptop.records.len(), 2);
+    assert!(laptop.conflicts.is_empty());
+
+    // The phone has never synced, it picks both up and adds one of its own.
+    let phone = server.sync(
+        "alice",
+        0,
+        vec![record(
+            "c",
+            &[
+                ("title", json!("Show C"), 150),
+                (DELETED, json!(false), 150),
+            ],
+        )],
+    );
+    assert_eq!(phone.records.len(), 3);
+
+    // The laptop renames A and the phone deletes B, neither sees a conflict.
+    let laptop = server.sync(
+        "alice",
+        200,
+        vec![record("a", &[("title", json!("Show A2"), 300)])],
+    );
+    assert!(laptop.conflicts.is_empty());
+    let phone = server.sync(
+        "alice",
+        200,
+        vec![record("b", &[(DELETED, json!(true), 310)])],
+    );
+    assert!(phone.conflicts.is_empty());
+    assert_eq!(value(&phone, "a", "title"), json!("Show A2"));
+    assert!(phone
+        .records
+        .iter()
+        .find(|r| r.uuid == "b")
+        .unwrap()
+        .deleted());
+
+    // Both change C's title since they last synced, the newer change wins and is reported.
+    server.sync(
+        "alice",
+        400,
+        vec![record("c", &[("title", json!("Laptop C"), 500)])],
+    );
+    let phone = server.sync(
+        "alice",
+        400,
+        vec![record("c", &[("title", json!("Phone C"), 450)])],
+    );
+    assert_eq!(value(&phone, "c", "title"), json!("Laptop C"));
+    assert_eq!(phone.conflicts.len(), 1);
+    assert!(!phone.conflicts[0].kept_local);
+
+    // Everything was saved, and other users don't see any of it.
+    let (status, body) = server.request("GET", "/sync/alice", None, "");
+    assert_eq!(status, 200);
+    let stored: SyncResponse = serde_json::from_str(&body).unwrap();
+    assert_eq!(stored.records, phone.records);
+    let (_, body) = server.request("GET", "/sync/bob", None, "");
+    assert!(serde_json::from_str::<SyncResponse>(&body)
+        .unwrap()
+        .records
+        .is_empty());
+}
+
+#[test]
+fn tokens_and_user_names_are_checked() {
+    let server = TestServer::start(Some("secret"));
+
+    assert_eq!(server.request("GET", "/sync/alice", None, "").0, 401);
+    assert_eq!(
+        server.request("GET", "/sync/alice", Some("wrong"), "").0,
+        401
+    );
+    assert_eq!(
+        server.request("GET", "/sync/alice", Some("secret"), "").0,
+        200
+    );
+    assert_eq!(
+        server
+            .request("GET", "/sync/..%2Fetc", Some("secret"), "")
+            .0,
+        404
+    );
+
+    // Errors come back as JSON, even when the message has quotes in it.
+    let (status, body) = server.request("POST", "/sync/alice", Some("secret"), r#"{"since": "x"}"#);
+    assert_eq!(status, 400);
+    let error: Value = serde_json::from_str(&body).expect("JSON error");
+    assert!(error["error"].as_str().unwrap().contains("\"x\""));
+
+    // Anything past 16 MiB is turned away before it's parsed.
+    let huge = " ".repeat(16 * 1024 * 1024 + 1);
+    assert_eq!(server.request("POST", "/sync/alice", Some("secret"), &huge).0, 413);
+}